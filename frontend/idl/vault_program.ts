@@ -14,43 +14,48 @@ export type VaultProgram = {
   },
   "instructions": [
     {
-      "name": "claimWithdraw",
+      "name": "acceptAdmin",
       "docs": [
-        "Claim withdrawal by transferring deposit tokens from vault to user.",
+        "Accept a pending admin transfer (pending-admin-only).",
         "",
         "Parameters:",
-        "- None (uses withdrawal ticket data)",
+        "- None (the signer must be vault_state.pending_admin)",
         "",
         "Security assumptions:",
-        "- Withdrawal ticket must exist and belong to the user",
-        "- Ticket must not be already claimed",
-        "- Current epoch must be >= unlock_epoch",
-        "- Vault must have sufficient deposit tokens"
+        "- Only the proposed admin can accept, proving control of the new key",
+        "- The pending proposal is cleared once accepted",
+        "- Every role, the treasury and the loss authority still held by the old admin move",
+        "to the new admin, so rotating a compromised admin key leaves it no power; roles",
+        "delegated to other keys are kept"
       ],
       "discriminator": [
-        232,
-        89,
-        154,
-        117,
-        16,
-        204,
-        182,
-        224
+        112,
+        42,
+        45,
+        90,
+        116,
+        181,
+        13,
+        170
       ],
       "accounts": [
         {
-          "name": "user",
+          "name": "pendingAdmin",
           "docs": [
-            "The user claiming the withdrawal (must sign)"
+            "The proposed admin (must sign and match vault_state.pending_admin)"
           ],
-          "writable": true,
-          "signer": true
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
         },
         {
           "name": "vaultState",
           "docs": [
-            "The vault state PDA"
+            "The vault state PDA (mutable to update admin, pending_admin and the roles moving",
+            "with the admin)"
           ],
+          "writable": true,
           "pda": {
             "seeds": [
               {
@@ -76,34 +81,130 @@ export type VaultProgram = {
               }
             ]
           }
-        },
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "advanceEpoch",
+      "docs": [
+        "Advance the vault to the next epoch (epoch-authority-only).",
+        "Withdrawal tickets unlock once current_epoch reaches their unlock_epoch.",
+        "",
+        "Parameters:",
+        "- None (the epoch is always incremented by one)",
+        "",
+        "Security assumptions:",
+        "- Only the epoch authority can call this instruction",
+        "- At least min_epoch_slots slots and min_epoch_seconds seconds must have passed since",
+        "the previous epoch started, so epochs cannot be spammed forward to skip the",
+        "withdrawal delay"
+      ],
+      "discriminator": [
+        93,
+        138,
+        234,
+        218,
+        241,
+        230,
+        132,
+        38
+      ],
+      "accounts": [
         {
-          "name": "depositMint",
+          "name": "epochAuthority",
           "docs": [
-            "The deposit token mint"
+            "The epoch authority (must sign and match vault_state.epoch_authority)"
           ],
+          "signer": true,
           "relations": [
             "vaultState"
           ]
         },
         {
-          "name": "vaultDepositTokenAccount",
+          "name": "vaultState",
           "docs": [
-            "Vault's deposit token account (source of transfer, owned by vault_state PDA)"
+            "The vault state PDA (mutable to update current_epoch and its start slot/time)"
           ],
-          "writable": true
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "applyLoss",
+      "docs": [
+        "Lower the exchange rate to socialize a loss or slashing event (rate manager + loss authority).",
+        "",
+        "Parameters:",
+        "- new_exchange_rate: New, lower exchange rate value (scaled by EXCHANGE_RATE_SCALE)",
+        "",
+        "Security assumptions:",
+        "- Both the rate manager and the loss authority must sign",
+        "- New exchange rate must be greater than zero and lower than the current rate",
+        "- Disabled in share-based mode, where losses show up in the vault balance",
+        "- Emits LossAppliedEvent so rate decreases can be monitored separately"
+      ],
+      "discriminator": [
+        170,
+        72,
+        150,
+        159,
+        55,
+        22,
+        103,
+        180
+      ],
+      "accounts": [
+        {
+          "name": "rateManager",
+          "docs": [
+            "The rate manager (must sign and match vault_state.rate_manager)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
         },
         {
-          "name": "userDepositTokenAccount",
+          "name": "lossAuthority",
           "docs": [
-            "User's deposit token account (destination of transfer)"
+            "The loss authority (must sign and match vault_state.loss_authority)"
           ],
-          "writable": true
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
         },
         {
-          "name": "withdrawalTicket",
+          "name": "vaultState",
           "docs": [
-            "Withdrawal ticket PDA"
+            "The vault state PDA (mutable to update exchange_rate)"
           ],
           "writable": true,
           "pda": {
@@ -111,73 +212,74 @@ export type VaultProgram = {
               {
                 "kind": "const",
                 "value": [
-                  119,
-                  105,
-                  116,
-                  104,
-                  100,
-                  114,
-                  97,
-                  119,
+                  118,
                   97,
+                  117,
                   108,
+                  116,
                   95,
+                  115,
                   116,
-                  105,
-                  99,
-                  107,
-                  101,
-                  116
+                  97,
+                  116,
+                  101
                 ]
               },
               {
                 "kind": "account",
-                "path": "user"
-              },
-              {
-                "kind": "account",
-                "path": "vaultState"
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
               }
             ]
           }
-        },
-        {
-          "name": "tokenProgram",
-          "docs": [
-            "Token program for transfers"
-          ]
         }
       ],
-      "args": []
+      "args": [
+        {
+          "name": "newExchangeRate",
+          "type": "u128"
+        }
+      ]
     },
     {
-      "name": "deposit",
+      "name": "claimLegacyWithdraw",
       "docs": [
-        "Deposit tokens into the vault and receive IOU tokens based on the current exchange rate.",
+        "Claim a withdrawal ticket opened by the original program (one unindexed ticket per",
+        "user and vault) and close it.",
         "",
         "Parameters:",
-        "- deposit_amount: Amount of deposit tokens to transfer to the vault",
+        "- min_assets_out: Minimum deposit tokens the user accepts (0 = no slippage check)",
         "",
         "Security assumptions:",
-        "- VaultState must be initialized",
-        "- User must have sufficient deposit tokens",
-        "- Exchange rate must be set (non-zero)"
+        "- The vault must already be migrated: vault_state only deserializes in the current",
+        "layout",
+        "- The ticket must be the user's legacy PDA (seeded without a ticket index), owned by",
+        "this program and in the 57-byte legacy layout",
+        "- A ticket the original program already paid out (claimed) is only closed",
+        "- Any other ticket must be unlocked and is priced like an unlocked ticket, at the",
+        "claim-time rate, without the exit fee, which did not exist when it was requested;",
+        "it releases the IOUs migrate_vault_state counted into pending_iou_amount, saturating",
+        "at zero so an undercounted migration cannot lock out the last legacy claimants",
+        "- The accrued management fee is collected before the claim is priced",
+        "- The ticket is closed on success (rent refunded to the user), so it cannot be",
+        "claimed twice",
+        "- Claims must not be paused"
       ],
       "discriminator": [
-        242,
-        35,
-        198,
-        137,
-        82,
-        225,
-        242,
-        182
+        200,
+        25,
+        183,
+        149,
+        17,
+        228,
+        133,
+        23
       ],
       "accounts": [
         {
           "name": "user",
           "docs": [
-            "The user making the deposit (must sign)"
+            "The user claiming the withdrawal (must sign, receives the ticket's rent)"
           ],
           "writable": true,
           "signer": true
@@ -185,7 +287,8 @@ export type VaultProgram = {
         {
           "name": "vaultState",
           "docs": [
-            "The vault state PDA"
+            "The vault state PDA, in the current layout (mutable to release the ticket's",
+            "pending IOUs)"
           ],
           "writable": true,
           "pda": {
@@ -226,7 +329,8 @@ export type VaultProgram = {
         {
           "name": "iouMint",
           "docs": [
-            "The IOU token mint"
+            "The IOU token mint (its supply prices the claim in share-based mode; management",
+            "fees are minted from it)"
           ],
           "writable": true,
           "relations": [
@@ -234,82 +338,133 @@ export type VaultProgram = {
           ]
         },
         {
-          "name": "userDepositTokenAccount",
+          "name": "vaultDepositTokenAccount",
           "docs": [
-            "User's deposit token account (source of transfer)"
+            "Vault's deposit token account (source of transfer, owned by vault_state PDA)"
           ],
           "writable": true
         },
         {
-          "name": "vaultDepositTokenAccount",
+          "name": "userDepositTokenAccount",
           "docs": [
-            "Vault's deposit token account (destination of transfer)"
+            "User's deposit token account (destination of transfer)"
           ],
           "writable": true
         },
         {
-          "name": "userIouTokenAccount",
+          "name": "treasuryIouTokenAccount",
           "docs": [
-            "User's IOU token account (destination of mint)"
+            "Treasury's IOU token account (receives the management fee; may be omitted while no",
+            "management fee is due)"
           ],
-          "writable": true
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "withdrawalTicket",
+          "docs": [
+            "Account<WithdrawalTicket> cannot deserialize. Validated by its seeds (no ticket",
+            "index) and owner here, and against its layout and user in the handler."
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  105,
+                  116,
+                  104,
+                  100,
+                  114,
+                  97,
+                  119,
+                  97,
+                  108,
+                  95,
+                  116,
+                  105,
+                  99,
+                  107,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "account",
+                "path": "vaultState"
+              }
+            ]
+          }
         },
         {
           "name": "tokenProgram",
           "docs": [
-            "Token program for transfers and mints"
+            "Token program for transfers and fee mints"
           ]
         }
       ],
       "args": [
         {
-          "name": "depositAmount",
+          "name": "minAssetsOut",
           "type": "u64"
         }
       ]
     },
     {
-      "name": "depositYield",
+      "name": "claimWithdraw",
       "docs": [
-        "Deposit yield tokens into the vault (admin-only).",
-        "This represents staking rewards, yield, or other income that benefits existing holders.",
-        "No IOU tokens are minted - the yield increases the value of existing IOUs.",
+        "Claim withdrawal by transferring deposit tokens from vault to user.",
         "",
         "Parameters:",
-        "- yield_amount: Amount of deposit tokens to transfer to the vault",
+        "- min_assets_out: Minimum deposit tokens the user accepts after the exit fee",
+        "(0 = no slippage check)",
         "",
         "Security assumptions:",
-        "- Only the admin can call this instruction",
-        "- Admin must have sufficient deposit tokens",
-        "- VaultState must be initialized"
+        "- Withdrawal ticket must exist and belong to the user",
+        "- Current epoch must be >= unlock_epoch",
+        "- Vault must have sufficient deposit tokens",
+        "- Tickets with a locked value are paid exactly the snapshotted amount; other tickets",
+        "cannot dip into assets reserved for locked tickets",
+        "- The exit fee is taken from the redeemed amount and sent to the treasury; without",
+        "the treasury's token accounts the claim only succeeds while no exit or management",
+        "fee is due, so a treasury that closed them cannot block exits from a fee-free vault",
+        "- The accrued management fee is collected before the claim is priced",
+        "- The ticket is closed on success (rent refunded to the user), so it cannot be claimed twice",
+        "- The claim reverts with SlippageExceeded if the user would receive fewer than",
+        "min_assets_out deposit tokens",
+        "- Claims must not be paused"
       ],
       "discriminator": [
+        232,
+        89,
+        154,
+        117,
+        16,
         204,
-        126,
-        164,
-        36,
-        57,
-        174,
-        68,
-        139
+        182,
+        224
       ],
       "accounts": [
         {
-          "name": "admin",
+          "name": "user",
           "docs": [
-            "The admin authority (must sign and match vault_state.admin)"
+            "The user claiming the withdrawal (must sign)"
           ],
           "writable": true,
-          "signer": true,
-          "relations": [
-            "vaultState"
-          ]
+          "signer": true
         },
         {
           "name": "vaultState",
           "docs": [
-            "The vault state PDA"
+            "The vault state PDA (mutable to release the ticket's pending IOUs)"
           ],
+          "writable": true,
           "pda": {
             "seeds": [
               {
@@ -340,74 +495,58 @@ export type VaultProgram = {
           "name": "depositMint",
           "docs": [
             "The deposit token mint"
+          ],
+          "relations": [
+            "vaultState"
           ]
         },
         {
-          "name": "adminDepositTokenAccount",
+          "name": "iouMint",
           "docs": [
-            "Admin's deposit token account (source of transfer)"
+            "The IOU token mint (its supply prices the claim in share-based mode; management",
+            "fees are minted from it)"
           ],
-          "writable": true
-        },
-        {
+          "writable": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
           "name": "vaultDepositTokenAccount",
           "docs": [
-            "Vault's deposit token account (destination of transfer, owned by vault_state PDA)"
+            "Vault's deposit token account (source of transfer, owned by vault_state PDA)"
           ],
           "writable": true
         },
         {
-          "name": "tokenProgram",
+          "name": "userDepositTokenAccount",
           "docs": [
-            "Token program for transfers"
-          ]
-        }
-      ],
-      "args": [
+            "User's deposit token account (destination of transfer)"
+          ],
+          "writable": true
+        },
         {
-          "name": "yieldAmount",
-          "type": "u64"
-        }
-      ]
-    },
-    {
-      "name": "increaseRate",
-      "docs": [
-        "Increase the exchange rate to simulate yield growth (admin-only).",
-        "",
-        "Parameters:",
-        "- new_exchange_rate: New exchange rate value (scaled by EXCHANGE_RATE_SCALE)",
-        "",
-        "Security assumptions:",
-        "- Only the admin can call this instruction",
-        "- New exchange rate must be greater than zero",
-        "- Exchange rate should typically increase to simulate yield"
-      ],
-      "discriminator": [
-        107,
-        159,
-        17,
-        45,
-        214,
-        117,
-        54,
-        254
-      ],
-      "accounts": [
+          "name": "treasuryDepositTokenAccount",
+          "docs": [
+            "Treasury's deposit token account (receives the exit fee; may be omitted while no",
+            "exit fee is charged)"
+          ],
+          "writable": true,
+          "optional": true
+        },
         {
-          "name": "admin",
+          "name": "treasuryIouTokenAccount",
           "docs": [
-            "The admin authority (must sign and match vault_state.admin)"
+            "Treasury's IOU token account (receives the management fee; may be omitted while no",
+            "management fee is due)"
           ],
-          "signer": true,
-          "relations": [
-            "vaultState"
-          ]
+          "writable": true,
+          "optional": true
         },
         {
-          "name": "vaultState",
+          "name": "userPosition",
           "docs": [
-            "The vault state PDA (mutable to update exchange_rate and current_epoch)"
+            "User position PDA (tracks the user's open tickets)"
           ],
           "writable": true,
           "pda": {
@@ -415,72 +554,122 @@ export type VaultProgram = {
               {
                 "kind": "const",
                 "value": [
-                  118,
-                  97,
                   117,
-                  108,
-                  116,
+                  115,
+                  101,
+                  114,
                   95,
+                  112,
+                  111,
                   115,
+                  105,
                   116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "account",
+                "path": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "withdrawalTicket",
+          "docs": [
+            "Withdrawal ticket PDA (selects which of the user's tickets to claim)",
+            "Closed after the claim, refunding its rent to the user"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  105,
+                  116,
+                  104,
+                  100,
+                  114,
+                  97,
+                  119,
                   97,
+                  108,
+                  95,
                   116,
-                  101
+                  105,
+                  99,
+                  107,
+                  101,
+                  116
                 ]
               },
               {
                 "kind": "account",
-                "path": "vault_state.deposit_mint",
-                "account": "vaultState"
+                "path": "user"
+              },
+              {
+                "kind": "account",
+                "path": "vaultState"
+              },
+              {
+                "kind": "account",
+                "path": "withdrawal_ticket.ticket_index",
+                "account": "withdrawalTicket"
               }
             ]
           }
+        },
+        {
+          "name": "tokenProgram",
+          "docs": [
+            "Token program for transfers and fee mints"
+          ]
         }
       ],
       "args": [
         {
-          "name": "newExchangeRate",
+          "name": "minAssetsOut",
           "type": "u64"
         }
       ]
     },
     {
-      "name": "initialize",
+      "name": "crankFees",
       "docs": [
-        "Initialize the vault with admin, deposit mint, and IOU mint.",
+        "Collect the management fee accrued since the last collection (permissionless).",
         "",
         "Parameters:",
-        "- None (all data comes from accounts)",
+        "- None",
         "",
         "Security assumptions:",
-        "- Admin must sign the transaction",
-        "- VaultState must not already exist (enforced by init constraint)",
-        "- Deposit mint and IOU mint must be valid token mints"
+        "- Anyone can call this instruction; the fee only depends on elapsed time and the",
+        "configured rate, and is always minted to the vault's treasury",
+        "- Calling it often cannot erase the fee: the accrual clock only advances once a",
+        "non-zero fee has been minted"
       ],
       "discriminator": [
-        175,
-        175,
-        109,
-        31,
-        13,
-        152,
-        155,
-        237
+        235,
+        7,
+        188,
+        90,
+        253,
+        46,
+        144,
+        32
       ],
       "accounts": [
-        {
-          "name": "admin",
-          "docs": [
-            "The admin authority that will control the vault (must sign and pay for account creation)"
-          ],
-          "writable": true,
-          "signer": true
-        },
         {
           "name": "vaultState",
           "docs": [
-            "The vault state PDA",
-            "Seeds: [\"vault_state\", deposit_mint]"
+            "The vault state PDA (mutable to update the exchange rate and accrual timestamp)"
           ],
           "writable": true,
           "pda": {
@@ -503,61 +692,78 @@ export type VaultProgram = {
               },
               {
                 "kind": "account",
-                "path": "depositMint"
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
               }
             ]
           }
         },
         {
-          "name": "depositMint",
+          "name": "iouMint",
           "docs": [
-            "The deposit token mint (used in PDA seeds)"
+            "The IOU token mint (management fees are minted from it)"
+          ],
+          "writable": true,
+          "relations": [
+            "vaultState"
           ]
         },
         {
-          "name": "iouMint",
+          "name": "treasuryIouTokenAccount",
           "docs": [
-            "The IOU token mint (stored in VaultState)"
-          ]
+            "Treasury's IOU token account (receives the management fee)"
+          ],
+          "writable": true
         },
         {
-          "name": "systemProgram",
+          "name": "tokenProgram",
           "docs": [
-            "System program for account creation"
-          ],
-          "address": "11111111111111111111111111111111"
+            "Token program for fee mints"
+          ]
         }
       ],
       "args": []
     },
     {
-      "name": "requestWithdraw",
+      "name": "deposit",
       "docs": [
-        "Request withdrawal by burning IOU tokens and creating a withdrawal ticket.",
+        "Deposit tokens into the vault and receive IOU tokens based on the current exchange rate.",
         "",
         "Parameters:",
-        "- iou_amount: Amount of IOU tokens to burn for withdrawal",
+        "- deposit_amount: Amount of deposit tokens to transfer to the vault",
+        "- min_iou_out: Minimum IOU tokens the user accepts (0 = no slippage check)",
         "",
         "Security assumptions:",
-        "- User must have sufficient IOU tokens",
-        "- User must not have an existing unclaimed withdrawal ticket",
-        "- VaultState must be initialized"
+        "- VaultState must be initialized",
+        "- User must have sufficient deposit tokens",
+        "- Exchange rate must be set (non-zero)",
+        "- In share-based mode the rate is derived from vault holdings before the transfer",
+        "- The entry fee is sent to the treasury and IOUs are minted for the remainder only;",
+        "the treasury's token accounts may be omitted while no entry or management fee is due",
+        "- The accrued management fee is collected before the deposit is priced",
+        "- The invested amount must fit under max_total_assets and, tracked in the user's",
+        "position, under max_deposit_per_user (0 disables either cap)",
+        "- Deposits below min_deposit_amount, or too small to mint a single IOU unit, are",
+        "rejected with DepositBelowMinimum",
+        "- The deposit reverts with SlippageExceeded if fewer than min_iou_out IOUs would be",
+        "minted, e.g. because the rate moved before the transaction landed",
+        "- Deposits must not be paused"
       ],
       "discriminator": [
+        242,
+        35,
+        198,
         137,
-        95,
-        187,
-        96,
-        250,
-        138,
-        31,
+        82,
+        225,
+        242,
         182
       ],
       "accounts": [
         {
           "name": "user",
           "docs": [
-            "The user requesting withdrawal (must sign)"
+            "The user making the deposit (must sign)"
           ],
           "writable": true,
           "signer": true
@@ -594,6 +800,15 @@ export type VaultProgram = {
             ]
           }
         },
+        {
+          "name": "depositMint",
+          "docs": [
+            "The deposit token mint"
+          ],
+          "relations": [
+            "vaultState"
+          ]
+        },
         {
           "name": "iouMint",
           "docs": [
@@ -604,18 +819,49 @@ export type VaultProgram = {
             "vaultState"
           ]
         },
+        {
+          "name": "userDepositTokenAccount",
+          "docs": [
+            "User's deposit token account (source of transfer)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "vaultDepositTokenAccount",
+          "docs": [
+            "Vault's deposit token account (destination of transfer)"
+          ],
+          "writable": true
+        },
         {
           "name": "userIouTokenAccount",
           "docs": [
-            "User's IOU token account (source of burn)"
+            "User's IOU token account (destination of mint)"
           ],
           "writable": true
         },
         {
-          "name": "withdrawalTicket",
+          "name": "treasuryDepositTokenAccount",
+          "docs": [
+            "Treasury's deposit token account (receives the entry fee; may be omitted while",
+            "no entry fee is charged)"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "treasuryIouTokenAccount",
+          "docs": [
+            "Treasury's IOU token account (receives the management fee; may be omitted while no",
+            "management fee is due)"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "userPosition",
           "docs": [
-            "Withdrawal ticket PDA (one per user per vault)",
-            "Space: 8 (discriminator) + 32 (user) + 8 (iou_amount) + 8 (unlock_epoch) + 1 (claimed) = 57"
+            "User position PDA (tracks the user's deposits against max_deposit_per_user)"
           ],
           "writable": true,
           "pda": {
@@ -623,23 +869,19 @@ export type VaultProgram = {
               {
                 "kind": "const",
                 "value": [
-                  119,
-                  105,
-                  116,
-                  104,
-                  100,
+                  117,
+                  115,
+                  101,
                   114,
-                  97,
-                  119,
-                  97,
-                  108,
                   95,
+                  112,
+                  111,
+                  115,
+                  105,
                   116,
                   105,
-                  99,
-                  107,
-                  101,
-                  116
+                  111,
+                  110
                 ]
               },
               {
@@ -656,140 +898,4074 @@ export type VaultProgram = {
         {
           "name": "tokenProgram",
           "docs": [
-            "Token program for burns"
+            "Token program for transfers and mints"
           ]
         },
         {
           "name": "systemProgram",
           "docs": [
-            "System program for account creation"
+            "System program for creating the user position"
           ],
           "address": "11111111111111111111111111111111"
         }
       ],
       "args": [
         {
-          "name": "iouAmount",
+          "name": "depositAmount",
+          "type": "u64"
+        },
+        {
+          "name": "minIouOut",
           "type": "u64"
         }
       ]
-    }
-  ],
-  "accounts": [
+    },
     {
-      "name": "vaultState",
-      "discriminator": [
-        228,
-        196,
-        82,
-        165,
-        98,
-        210,
-        235,
-        152
+      "name": "depositYield",
+      "docs": [
+        "Deposit yield tokens into the vault (yield-depositor-only).",
+        "This represents staking rewards, yield, or other income that benefits existing holders.",
+        "No IOU tokens are minted - the yield increases the value of existing IOUs.",
+        "In share-based mode the exchange rate rises automatically with the added assets.",
+        "",
+        "Parameters:",
+        "- yield_amount: Amount of deposit tokens to transfer to the vault",
+        "",
+        "Security assumptions:",
+        "- Only the yield depositor can call this instruction",
+        "- Yield depositor must have sufficient deposit tokens",
+        "- VaultState must be initialized",
+        "- Any gain above the high-water mark pays the performance fee (see",
+        "VaultState::accrue_performance_fee)"
+      ],
+      "discriminator": [
+        204,
+        126,
+        164,
+        36,
+        57,
+        174,
+        68,
+        139
+      ],
+      "accounts": [
+        {
+          "name": "yieldDepositor",
+          "docs": [
+            "The yield depositor (must sign and match vault_state.yield_depositor)"
+          ],
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to record the derived exchange rate)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "depositMint",
+          "docs": [
+            "The deposit token mint"
+          ],
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "iouMint",
+          "docs": [
+            "The IOU token mint (its supply prices the yield in share-based mode; performance",
+            "fees are minted from it)"
+          ],
+          "writable": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "yieldDepositorTokenAccount",
+          "docs": [
+            "Yield depositor's deposit token account (source of transfer)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "vaultDepositTokenAccount",
+          "docs": [
+            "Vault's deposit token account (destination of transfer, owned by vault_state PDA)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "treasuryIouTokenAccount",
+          "docs": [
+            "Treasury's IOU token account (receives the performance fee)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "tokenProgram",
+          "docs": [
+            "Token program for transfers and fee mints"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "yieldAmount",
+          "type": "u64"
+        }
       ]
     },
     {
-      "name": "withdrawalTicket",
+      "name": "increaseRate",
+      "docs": [
+        "Increase the exchange rate to simulate yield growth (rate-manager-only).",
+        "The epoch is not affected; use advance_epoch for that.",
+        "",
+        "Parameters:",
+        "- new_exchange_rate: New exchange rate value (scaled by EXCHANGE_RATE_SCALE)",
+        "",
+        "Security assumptions:",
+        "- Only the rate manager can call this instruction",
+        "- New exchange rate must not be lower than the current rate (use apply_loss)",
+        "- New exchange rate must stay within max_rate_increase_bps of the rate the",
+        "current epoch started at",
+        "- Disabled in share-based mode, where the rate tracks vault holdings",
+        "- Any gain above the high-water mark pays the performance fee (see",
+        "VaultState::accrue_performance_fee)"
+      ],
       "discriminator": [
-        92,
-        140,
-        181,
-        69,
-        244,
-        220,
-        233,
-        156
+        107,
+        159,
+        17,
+        45,
+        214,
+        117,
+        54,
+        254
+      ],
+      "accounts": [
+        {
+          "name": "rateManager",
+          "docs": [
+            "The rate manager (must sign and match vault_state.rate_manager)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update exchange_rate)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "iouMint",
+          "docs": [
+            "The IOU token mint (performance fees are minted from it)"
+          ],
+          "writable": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "treasuryIouTokenAccount",
+          "docs": [
+            "Treasury's IOU token account (receives the performance fee)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "tokenProgram",
+          "docs": [
+            "Token program for fee mints"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "newExchangeRate",
+          "type": "u128"
+        }
       ]
-    }
-  ],
-  "errors": [
+    },
     {
-      "code": 6000,
-      "name": "invalidExchangeRate",
-      "msg": "Invalid exchange rate"
+      "name": "initialize",
+      "docs": [
+        "Initialize the vault with admin, deposit mint, and IOU mint.",
+        "",
+        "Parameters:",
+        "- None (all data comes from accounts)",
+        "",
+        "Security assumptions:",
+        "- Admin must sign the transaction",
+        "- VaultState must not already exist (enforced by init constraint)",
+        "- Deposit mint and IOU mint must be valid token mints",
+        "- The IOU mint must already be controlled by the vault: vault_state is its mint",
+        "authority, it has no freeze authority, no supply, and the deposit mint's decimals",
+        "- The vault's deposit token account is the vault_state's associated token account,",
+        "created here if needed; every other instruction only accepts that account"
+      ],
+      "discriminator": [
+        175,
+        175,
+        109,
+        31,
+        13,
+        152,
+        155,
+        237
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority that will control the vault (must sign and pay for account creation)"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA",
+            "Seeds: [\"vault_state\", deposit_mint]"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "depositMint"
+              }
+            ]
+          }
+        },
+        {
+          "name": "depositMint",
+          "docs": [
+            "The deposit token mint (used in PDA seeds)"
+          ]
+        },
+        {
+          "name": "iouMint",
+          "docs": [
+            "The IOU token mint (stored in VaultState)",
+            "Must be mintable only by the vault, unfreezable and unissued, so every IOU in",
+            "circulation is backed by a deposit"
+          ]
+        },
+        {
+          "name": "vaultDepositTokenAccount",
+          "docs": [
+            "The vault's deposit token account: the vault_state's associated token account.",
+            "init_if_needed because anyone can create an associated token account, so a",
+            "pre-created one must not block initialization"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "vaultState"
+              },
+              {
+                "kind": "account",
+                "path": "tokenProgram"
+              },
+              {
+                "kind": "account",
+                "path": "depositMint"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                140,
+                151,
+                37,
+                143,
+                78,
+                36,
+                137,
+                241,
+                187,
+                61,
+                16,
+                41,
+                20,
+                142,
+                13,
+                131,
+                11,
+                90,
+                19,
+                153,
+                218,
+                255,
+                16,
+                132,
+                4,
+                142,
+                123,
+                216,
+                219,
+                233,
+                248,
+                89
+              ]
+            }
+          }
+        },
+        {
+          "name": "tokenProgram",
+          "docs": [
+            "Token program that owns the deposit mint"
+          ]
+        },
+        {
+          "name": "associatedTokenProgram",
+          "docs": [
+            "Associated token program for creating the vault token account"
+          ],
+          "address": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+        },
+        {
+          "name": "systemProgram",
+          "docs": [
+            "System program for account creation"
+          ],
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "migrateVaultState",
+      "docs": [
+        "Migrate a vault state written by an older program version to the current layout",
+        "(admin-only).",
+        "",
+        "Parameters:",
+        "- legacy_pending_iou_amount: Total IOU tokens burned into withdrawal tickets the",
+        "original program has not paid out yet (claimed = false); they stay outstanding",
+        "until claim_legacy_withdraw pays each ticket",
+        "",
+        "Security assumptions:",
+        "- Only the admin recorded in the old account can call this instruction",
+        "- legacy_pending_iou_amount is trusted from the admin and should match the unpaid",
+        "legacy tickets on chain. Legacy claims release it saturating at zero, so too low",
+        "never blocks a claim and only overvalues IOUs in share-based mode until those",
+        "tickets are paid; too high undervalues them for good, so when in doubt undercount",
+        "- The account must be the vault_state PDA of deposit_mint, owned by this program;",
+        "its layout is identified by its size (unversioned legacy layout) or its version,",
+        "and an account already at VAULT_STATE_VERSION is rejected with AlreadyMigrated,",
+        "so a vault is never rewritten twice",
+        "- The legacy rate is rescaled from LEGACY_EXCHANGE_RATE_SCALE to EXCHANGE_RATE_SCALE,",
+        "so every price is unchanged; fields missing from the old layout get the values",
+        "initialize would set (admin roles and treasury, fees off, epoch and fee clocks",
+        "starting now, high-water mark and epoch start rate at the current rate)",
+        "- The supplied vault token account (deposit mint, owned by vault_state) is recorded",
+        "as the vault's canonical token account, so it must be the one holding the assets",
+        "- The admin pays the rent if the account grows"
+      ],
+      "discriminator": [
+        102,
+        131,
+        230,
+        150,
+        169,
+        240,
+        189,
+        142
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign, match the stored vault_state.admin and pay any",
+            "extra rent)"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "depositMint",
+          "docs": [
+            "The deposit token mint the vault was created for"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "deserialize. Validated by its seeds and owner here, and against its layout,",
+            "version and admin in the handler."
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "depositMint"
+              }
+            ]
+          }
+        },
+        {
+          "name": "vaultDepositTokenAccount",
+          "docs": [
+            "The vault's existing deposit token account, recorded in the migrated state"
+          ]
+        },
+        {
+          "name": "systemProgram",
+          "docs": [
+            "System program (funds the extra rent)"
+          ],
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "legacyPendingIouAmount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "mintShares",
+      "docs": [
+        "Mint an exact amount of IOU tokens, paying whatever deposit tokens that costs at the",
+        "current exchange rate.",
+        "",
+        "Parameters:",
+        "- iou_amount: Amount of IOU tokens to mint to the user",
+        "- max_assets_in: Maximum deposit tokens the user pays, entry fee included",
+        "",
+        "Security assumptions:",
+        "- Same checks as deposit (pause flag, caps, minimum deposit, management fee)",
+        "- The required deposit is rounded up, both for the rate and for the entry fee, so",
+        "the vault never mints IOUs worth more than it receives",
+        "- The instruction reverts with SlippageExceeded if the cost exceeds max_assets_in"
+      ],
+      "discriminator": [
+        24,
+        196,
+        132,
+        0,
+        183,
+        158,
+        216,
+        142
+      ],
+      "accounts": [
+        {
+          "name": "user",
+          "docs": [
+            "The user making the deposit (must sign)"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "depositMint",
+          "docs": [
+            "The deposit token mint"
+          ],
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "iouMint",
+          "docs": [
+            "The IOU token mint"
+          ],
+          "writable": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "userDepositTokenAccount",
+          "docs": [
+            "User's deposit token account (source of transfer)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "vaultDepositTokenAccount",
+          "docs": [
+            "Vault's deposit token account (destination of transfer)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "userIouTokenAccount",
+          "docs": [
+            "User's IOU token account (destination of mint)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "treasuryDepositTokenAccount",
+          "docs": [
+            "Treasury's deposit token account (receives the entry fee; may be omitted while",
+            "no entry fee is charged)"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "treasuryIouTokenAccount",
+          "docs": [
+            "Treasury's IOU token account (receives the management fee; may be omitted while no",
+            "management fee is due)"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "userPosition",
+          "docs": [
+            "User position PDA (tracks the user's deposits against max_deposit_per_user)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  112,
+                  111,
+                  115,
+                  105,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "account",
+                "path": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "tokenProgram",
+          "docs": [
+            "Token program for transfers and mints"
+          ]
+        },
+        {
+          "name": "systemProgram",
+          "docs": [
+            "System program for creating the user position"
+          ],
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "iouAmount",
+          "type": "u64"
+        },
+        {
+          "name": "maxAssetsIn",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "pause",
+      "docs": [
+        "Pause one or more vault operations (pauser-only).",
+        "",
+        "Parameters:",
+        "- flags: Bitmask of PAUSE_DEPOSIT, PAUSE_REQUEST_WITHDRAW and PAUSE_CLAIM to set",
+        "",
+        "Security assumptions:",
+        "- Only the pauser can call this instruction",
+        "- Flags are independent, so deposits can be frozen while users can still exit",
+        "(or vice versa)"
+      ],
+      "discriminator": [
+        211,
+        22,
+        221,
+        251,
+        74,
+        121,
+        193,
+        47
+      ],
+      "accounts": [
+        {
+          "name": "pauser",
+          "docs": [
+            "The pauser (must sign and match vault_state.pauser)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update paused_flags)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "flags",
+          "type": "u8"
+        }
+      ]
+    },
+    {
+      "name": "proposeAdmin",
+      "docs": [
+        "Propose a new admin (admin-only). Takes effect once the new admin calls accept_admin.",
+        "",
+        "Parameters:",
+        "- new_admin: Key proposed as the next admin (Pubkey::default() cancels a pending proposal)",
+        "",
+        "Security assumptions:",
+        "- Only the current admin can call this instruction",
+        "- The current admin stays in control until the proposal is accepted, so a mistyped",
+        "key cannot lock the vault"
+      ],
+      "discriminator": [
+        121,
+        214,
+        199,
+        212,
+        87,
+        39,
+        117,
+        234
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign and match vault_state.admin)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update pending_admin)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "newAdmin",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "requestWithdraw",
+      "docs": [
+        "Request withdrawal by burning IOU tokens and creating a withdrawal ticket.",
+        "",
+        "Parameters:",
+        "- iou_amount: Amount of IOU tokens to burn for withdrawal",
+        "",
+        "Security assumptions:",
+        "- User must have sufficient IOU tokens",
+        "- Each request opens a new ticket at the user's next ticket index, so several",
+        "tickets can be pending at once",
+        "- VaultState must be initialized",
+        "- When lock_withdrawal_value is set, the owed deposit amount is fixed at the",
+        "request-time rate and reserved in pending_withdrawal_assets",
+        "- The accrued management fee is collected before the ticket is priced",
+        "- Requests below min_redeem_iou_amount are rejected with RedeemBelowMinimum unless",
+        "they redeem the user's whole IOU balance, so small tickets cannot be spammed but",
+        "leftover balances can always exit",
+        "- Withdrawal requests must not be paused"
+      ],
+      "discriminator": [
+        137,
+        95,
+        187,
+        96,
+        250,
+        138,
+        31,
+        182
+      ],
+      "accounts": [
+        {
+          "name": "user",
+          "docs": [
+            "The user requesting withdrawal (must sign)"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "iouMint",
+          "docs": [
+            "The IOU token mint"
+          ],
+          "writable": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "userIouTokenAccount",
+          "docs": [
+            "User's IOU token account (source of burn)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "vaultDepositTokenAccount",
+          "docs": [
+            "Vault's deposit token account (its balance prices locked tickets in share-based mode)"
+          ]
+        },
+        {
+          "name": "treasuryIouTokenAccount",
+          "docs": [
+            "Treasury's IOU token account (receives the management fee; may be omitted while no",
+            "management fee is due)"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "userPosition",
+          "docs": [
+            "User position PDA (one per user per vault, created on first deposit or request)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  112,
+                  111,
+                  115,
+                  105,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "account",
+                "path": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "withdrawalTicket",
+          "docs": [
+            "Withdrawal ticket PDA (one per user per vault per ticket index)",
+            "Created fresh on every request and closed on claim, so tickets are never reused",
+            "Space: WithdrawalTicket::LEN (summed field by field next to the struct)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  105,
+                  116,
+                  104,
+                  100,
+                  114,
+                  97,
+                  119,
+                  97,
+                  108,
+                  95,
+                  116,
+                  105,
+                  99,
+                  107,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "account",
+                "path": "vaultState"
+              },
+              {
+                "kind": "account",
+                "path": "user_position.next_ticket_index",
+                "account": "userPosition"
+              }
+            ]
+          }
+        },
+        {
+          "name": "tokenProgram",
+          "docs": [
+            "Token program for burns and fee mints"
+          ]
+        },
+        {
+          "name": "systemProgram",
+          "docs": [
+            "System program for account creation"
+          ],
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "iouAmount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "requestWithdrawAssets",
+      "docs": [
+        "Request a withdrawal of a deposit token amount, burning the IOU tokens it is worth",
+        "at the current exchange rate and creating a normal withdrawal ticket.",
+        "",
+        "Parameters:",
+        "- asset_amount: Deposit tokens to redeem, before the exit fee taken at claim time",
+        "- max_iou_burn: Maximum IOU tokens the user accepts to burn",
+        "",
+        "Security assumptions:",
+        "- Same checks as request_withdraw (pause flag, minimum, management fee)",
+        "- The IOU amount is rounded up, so the ticket is never worth less than asset_amount",
+        "at the request-time rate and the user cannot redeem value they did not burn",
+        "- The ticket is priced like any other: unlocked tickets are paid at the claim-time",
+        "rate, locked tickets the snapshotted amount",
+        "- The request reverts with SlippageExceeded if more than max_iou_burn IOUs are needed"
+      ],
+      "discriminator": [
+        10,
+        140,
+        200,
+        180,
+        92,
+        59,
+        30,
+        195
+      ],
+      "accounts": [
+        {
+          "name": "user",
+          "docs": [
+            "The user requesting withdrawal (must sign)"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "iouMint",
+          "docs": [
+            "The IOU token mint"
+          ],
+          "writable": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "userIouTokenAccount",
+          "docs": [
+            "User's IOU token account (source of burn)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "vaultDepositTokenAccount",
+          "docs": [
+            "Vault's deposit token account (its balance prices locked tickets in share-based mode)"
+          ]
+        },
+        {
+          "name": "treasuryIouTokenAccount",
+          "docs": [
+            "Treasury's IOU token account (receives the management fee; may be omitted while no",
+            "management fee is due)"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "userPosition",
+          "docs": [
+            "User position PDA (one per user per vault, created on first deposit or request)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  112,
+                  111,
+                  115,
+                  105,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "account",
+                "path": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "withdrawalTicket",
+          "docs": [
+            "Withdrawal ticket PDA (one per user per vault per ticket index)",
+            "Created fresh on every request and closed on claim, so tickets are never reused",
+            "Space: WithdrawalTicket::LEN (summed field by field next to the struct)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  105,
+                  116,
+                  104,
+                  100,
+                  114,
+                  97,
+                  119,
+                  97,
+                  108,
+                  95,
+                  116,
+                  105,
+                  99,
+                  107,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "account",
+                "path": "vaultState"
+              },
+              {
+                "kind": "account",
+                "path": "user_position.next_ticket_index",
+                "account": "userPosition"
+              }
+            ]
+          }
+        },
+        {
+          "name": "tokenProgram",
+          "docs": [
+            "Token program for burns and fee mints"
+          ]
+        },
+        {
+          "name": "systemProgram",
+          "docs": [
+            "System program for account creation"
+          ],
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "assetAmount",
+          "type": "u64"
+        },
+        {
+          "name": "maxIouBurn",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setDepositCaps",
+      "docs": [
+        "Set the deposit caps (admin-only).",
+        "",
+        "Parameters:",
+        "- max_total_assets: Maximum deposit tokens managed by the vault (0 = unlimited)",
+        "- max_deposit_per_user: Maximum outstanding deposits per user position (0 = unlimited)",
+        "",
+        "Security assumptions:",
+        "- Only the admin can call this instruction",
+        "- Lowering a cap below current holdings only blocks new deposits; withdrawals are",
+        "never affected"
+      ],
+      "discriminator": [
+        14,
+        5,
+        41,
+        181,
+        21,
+        175,
+        64,
+        175
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign and match vault_state.admin)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the deposit caps)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "maxTotalAssets",
+          "type": "u64"
+        },
+        {
+          "name": "maxDepositPerUser",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setEpochConfig",
+      "docs": [
+        "Configure the minimum epoch duration (admin-only). The key allowed to advance epochs",
+        "is the EpochAuthority role, assigned with set_role.",
+        "",
+        "Parameters:",
+        "- min_epoch_slots: Minimum number of slots between epoch advances",
+        "- min_epoch_seconds: Minimum number of seconds between epoch advances",
+        "",
+        "Security assumptions:",
+        "- Only the admin can call this instruction",
+        "- min_epoch_seconds must not be negative",
+        "- Vaults start with a one-day minimum in both slots and seconds; setting both to 0",
+        "lets the epoch authority advance epochs back to back"
+      ],
+      "discriminator": [
+        143,
+        162,
+        6,
+        215,
+        102,
+        188,
+        43,
+        82
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign and match vault_state.admin)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the epoch configuration)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "minEpochSlots",
+          "type": "u64"
+        },
+        {
+          "name": "minEpochSeconds",
+          "type": "i64"
+        }
+      ]
+    },
+    {
+      "name": "setFees",
+      "docs": [
+        "Configure the entry and exit fees and the treasury that receives them (fee-manager-only).",
+        "",
+        "Parameters:",
+        "- entry_fee_bps: Fee taken from each deposit, in basis points",
+        "- exit_fee_bps: Fee taken from each claimed withdrawal, in basis points",
+        "- treasury: Owner of the token accounts that receive fees",
+        "",
+        "Security assumptions:",
+        "- Only the fee manager can call this instruction",
+        "- Both fees are capped at MAX_FEE_BPS, so a compromised fee manager cannot",
+        "confiscate deposits",
+        "- The treasury must be a real key (not Pubkey::default())",
+        "- The treasury must already own a deposit token account and an IOU token account",
+        "(passed in and checked for mint and owner), since every fee is routed to them;",
+        "user instructions fail with TreasuryAccountMissing if a fee is due and they are",
+        "not supplied"
+      ],
+      "discriminator": [
+        137,
+        178,
+        49,
+        58,
+        0,
+        245,
+        242,
+        190
+      ],
+      "accounts": [
+        {
+          "name": "feeManager",
+          "docs": [
+            "The fee manager (must sign and match vault_state.fee_manager)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the fee configuration)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasuryDepositTokenAccount",
+          "docs": [
+            "New treasury's deposit token account (will receive entry and exit fees)"
+          ]
+        },
+        {
+          "name": "treasuryIouTokenAccount",
+          "docs": [
+            "New treasury's IOU token account (will receive performance and management fees)"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "entryFeeBps",
+          "type": "u16"
+        },
+        {
+          "name": "exitFeeBps",
+          "type": "u16"
+        },
+        {
+          "name": "treasury",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "setLockWithdrawalValue",
+      "docs": [
+        "Choose whether new withdrawal tickets lock their redemption value at request time (admin-only).",
+        "",
+        "Parameters:",
+        "- enabled: true to snapshot the owed deposit amount into new tickets,",
+        "false to price new tickets at the claim-time exchange rate",
+        "",
+        "Security assumptions:",
+        "- Only the admin can call this instruction",
+        "- Existing tickets keep the pricing they were created with"
+      ],
+      "discriminator": [
+        68,
+        120,
+        59,
+        30,
+        149,
+        220,
+        129,
+        174
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign and match vault_state.admin)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update lock_withdrawal_value)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "enabled",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "setManagementFee",
+      "docs": [
+        "Configure the annual management fee (fee-manager-only).",
+        "",
+        "Parameters:",
+        "- management_fee_bps: Annual fee on vault value, in basis points",
+        "",
+        "Security assumptions:",
+        "- Only the fee manager can call this instruction",
+        "- The fee is capped at MAX_MANAGEMENT_FEE_BPS",
+        "- The fee accrued so far is collected at the old rate first, so the new rate",
+        "never applies retroactively"
+      ],
+      "discriminator": [
+        48,
+        23,
+        111,
+        131,
+        80,
+        204,
+        69,
+        145
+      ],
+      "accounts": [
+        {
+          "name": "feeManager",
+          "docs": [
+            "The fee manager (must sign and match vault_state.fee_manager)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the fee configuration)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "iouMint",
+          "docs": [
+            "The IOU token mint (management fees are minted from it)"
+          ],
+          "writable": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "treasuryIouTokenAccount",
+          "docs": [
+            "Treasury's IOU token account (receives the management fee)"
+          ],
+          "writable": true
+        },
+        {
+          "name": "tokenProgram",
+          "docs": [
+            "Token program for fee mints"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "managementFeeBps",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "setMinimums",
+      "docs": [
+        "Set the minimum deposit and redemption amounts (admin-only).",
+        "",
+        "Parameters:",
+        "- min_deposit_amount: Smallest deposit accepted, in deposit tokens (0 = no minimum)",
+        "- min_redeem_iou_amount: Smallest withdrawal request accepted, in IOU tokens",
+        "(0 = no minimum)",
+        "",
+        "Security assumptions:",
+        "- Only the admin can call this instruction",
+        "- Users can always redeem their whole IOU balance, so a high minimum cannot trap funds"
+      ],
+      "discriminator": [
+        144,
+        180,
+        111,
+        22,
+        25,
+        13,
+        248,
+        14
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign and match vault_state.admin)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the minimums)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "minDepositAmount",
+          "type": "u64"
+        },
+        {
+          "name": "minRedeemIouAmount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setPerformanceFee",
+      "docs": [
+        "Configure the performance fee charged on exchange rate gains (fee-manager-only).",
+        "",
+        "Parameters:",
+        "- performance_fee_bps: Share of each gain above the high-water mark paid to the",
+        "treasury, in basis points",
+        "",
+        "Security assumptions:",
+        "- Only the fee manager can call this instruction",
+        "- The fee is capped at MAX_PERFORMANCE_FEE_BPS",
+        "- The high-water mark is left untouched, so changing the fee never charges gains",
+        "that were already accounted for"
+      ],
+      "discriminator": [
+        129,
+        89,
+        113,
+        1,
+        18,
+        68,
+        109,
+        22
+      ],
+      "accounts": [
+        {
+          "name": "feeManager",
+          "docs": [
+            "The fee manager (must sign and match vault_state.fee_manager)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the fee configuration)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "performanceFeeBps",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "setRateConfig",
+      "docs": [
+        "Configure the per-epoch rate increase bound and the loss authority (admin-only).",
+        "",
+        "Parameters:",
+        "- max_rate_increase_bps: Maximum increase of the exchange rate per epoch, in basis points",
+        "- loss_authority: Key that must co-sign apply_loss (Pubkey::default() disables losses)",
+        "",
+        "Security assumptions:",
+        "- Only the admin can call this instruction",
+        "- max_rate_increase_bps must not exceed BPS_DENOMINATOR (a 100% increase)"
+      ],
+      "discriminator": [
+        177,
+        53,
+        58,
+        154,
+        46,
+        142,
+        164,
+        187
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign and match vault_state.admin)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the rate configuration)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "maxRateIncreaseBps",
+          "type": "u16"
+        },
+        {
+          "name": "lossAuthority",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "setRole",
+      "docs": [
+        "Assign an operational role to a key (admin-only).",
+        "",
+        "Parameters:",
+        "- role: The role to assign",
+        "- authority: Key that will hold the role",
+        "",
+        "Security assumptions:",
+        "- Only the admin can call this instruction",
+        "- Each role only grants access to its own instructions, so ops keys do not",
+        "need the admin key"
+      ],
+      "discriminator": [
+        77,
+        78,
+        62,
+        233,
+        192,
+        61,
+        199,
+        190
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign and match vault_state.admin)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the role)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "role",
+          "type": {
+            "defined": {
+              "name": "vaultProgramVaultRole"
+            }
+          }
+        },
+        {
+          "name": "authority",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "setShareBasedAccounting",
+      "docs": [
+        "Switch between manual and share-based (ERC-4626 style) accounting (admin-only).",
+        "",
+        "Parameters:",
+        "- enabled: true to derive the exchange rate from vault assets / outstanding IOUs,",
+        "false to go back to the admin-set rate",
+        "",
+        "Security assumptions:",
+        "- Only the admin can call this instruction",
+        "- The stored rate is re-anchored to the asset-backed rate on the switch, so pricing",
+        "stays continuous when leaving share-based mode"
+      ],
+      "discriminator": [
+        19,
+        24,
+        57,
+        133,
+        11,
+        117,
+        87,
+        69
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "docs": [
+            "The admin authority (must sign and match vault_state.admin)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update the accounting mode and exchange_rate)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "iouMint",
+          "docs": [
+            "The IOU token mint (its supply is the outstanding share count)"
+          ],
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultDepositTokenAccount",
+          "docs": [
+            "Vault's deposit token account (its balance is the vault's managed assets)"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "enabled",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "unpause",
+      "docs": [
+        "Unpause one or more vault operations (pauser-only).",
+        "",
+        "Parameters:",
+        "- flags: Bitmask of PAUSE_DEPOSIT, PAUSE_REQUEST_WITHDRAW and PAUSE_CLAIM to clear",
+        "",
+        "Security assumptions:",
+        "- Only the pauser can call this instruction"
+      ],
+      "discriminator": [
+        169,
+        144,
+        4,
+        38,
+        10,
+        141,
+        188,
+        255
+      ],
+      "accounts": [
+        {
+          "name": "pauser",
+          "docs": [
+            "The pauser (must sign and match vault_state.pauser)"
+          ],
+          "signer": true,
+          "relations": [
+            "vaultState"
+          ]
+        },
+        {
+          "name": "vaultState",
+          "docs": [
+            "The vault state PDA (mutable to update paused_flags)"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  118,
+                  97,
+                  117,
+                  108,
+                  116,
+                  95,
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "vault_state.deposit_mint",
+                "account": "vaultState"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "flags",
+          "type": "u8"
+        }
+      ]
+    }
+  ],
+  "accounts": [
+    {
+      "name": "userPosition",
+      "discriminator": [
+        251,
+        248,
+        209,
+        245,
+        83,
+        234,
+        17,
+        27
+      ]
+    },
+    {
+      "name": "vaultState",
+      "discriminator": [
+        228,
+        196,
+        82,
+        165,
+        98,
+        210,
+        235,
+        152
+      ]
+    },
+    {
+      "name": "withdrawalTicket",
+      "discriminator": [
+        92,
+        140,
+        181,
+        69,
+        244,
+        220,
+        233,
+        156
+      ]
+    }
+  ],
+  "events": [
+    {
+      "name": "adminAcceptedEvent",
+      "discriminator": [
+        14,
+        221,
+        60,
+        92,
+        241,
+        147,
+        95,
+        66
+      ]
+    },
+    {
+      "name": "adminProposedEvent",
+      "discriminator": [
+        212,
+        163,
+        91,
+        28,
+        223,
+        95,
+        2,
+        102
+      ]
+    },
+    {
+      "name": "depositCapsUpdatedEvent",
+      "discriminator": [
+        195,
+        54,
+        216,
+        92,
+        113,
+        196,
+        83,
+        7
+      ]
+    },
+    {
+      "name": "depositEvent",
+      "discriminator": [
+        120,
+        248,
+        61,
+        83,
+        31,
+        142,
+        107,
+        144
+      ]
+    },
+    {
+      "name": "epochAdvancedEvent",
+      "discriminator": [
+        26,
+        197,
+        195,
+        116,
+        126,
+        48,
+        210,
+        42
+      ]
+    },
+    {
+      "name": "epochConfigUpdatedEvent",
+      "discriminator": [
+        55,
+        114,
+        129,
+        184,
+        206,
+        146,
+        53,
+        137
+      ]
+    },
+    {
+      "name": "feesUpdatedEvent",
+      "discriminator": [
+        132,
+        181,
+        254,
+        193,
+        136,
+        177,
+        41,
+        20
+      ]
+    },
+    {
+      "name": "legacyWithdrawClaimedEvent",
+      "discriminator": [
+        222,
+        169,
+        173,
+        251,
+        7,
+        52,
+        138,
+        244
+      ]
+    },
+    {
+      "name": "lockWithdrawalValueUpdatedEvent",
+      "discriminator": [
+        169,
+        156,
+        156,
+        146,
+        113,
+        225,
+        16,
+        208
+      ]
+    },
+    {
+      "name": "lossAppliedEvent",
+      "discriminator": [
+        161,
+        255,
+        128,
+        26,
+        147,
+        133,
+        19,
+        205
+      ]
+    },
+    {
+      "name": "managementFeeAccruedEvent",
+      "discriminator": [
+        244,
+        211,
+        35,
+        137,
+        18,
+        186,
+        33,
+        31
+      ]
+    },
+    {
+      "name": "managementFeeUpdatedEvent",
+      "discriminator": [
+        186,
+        233,
+        134,
+        138,
+        239,
+        3,
+        87,
+        93
+      ]
+    },
+    {
+      "name": "minimumsUpdatedEvent",
+      "discriminator": [
+        237,
+        3,
+        103,
+        98,
+        213,
+        156,
+        38,
+        31
+      ]
+    },
+    {
+      "name": "pauseFlagsUpdatedEvent",
+      "discriminator": [
+        154,
+        173,
+        89,
+        112,
+        104,
+        11,
+        213,
+        239
+      ]
+    },
+    {
+      "name": "performanceFeeAccruedEvent",
+      "discriminator": [
+        158,
+        229,
+        248,
+        182,
+        123,
+        31,
+        132,
+        120
+      ]
+    },
+    {
+      "name": "performanceFeeUpdatedEvent",
+      "discriminator": [
+        22,
+        114,
+        196,
+        167,
+        33,
+        136,
+        160,
+        71
+      ]
+    },
+    {
+      "name": "rateConfigUpdatedEvent",
+      "discriminator": [
+        100,
+        144,
+        215,
+        96,
+        178,
+        46,
+        27,
+        245
+      ]
+    },
+    {
+      "name": "rateUpdatedEvent",
+      "discriminator": [
+        195,
+        250,
+        67,
+        203,
+        224,
+        247,
+        94,
+        25
+      ]
+    },
+    {
+      "name": "roleUpdatedEvent",
+      "discriminator": [
+        148,
+        192,
+        229,
+        187,
+        121,
+        51,
+        231,
+        122
+      ]
+    },
+    {
+      "name": "shareBasedAccountingUpdatedEvent",
+      "discriminator": [
+        10,
+        78,
+        231,
+        49,
+        31,
+        80,
+        225,
+        11
+      ]
+    },
+    {
+      "name": "vaultInitializedEvent",
+      "discriminator": [
+        203,
+        214,
+        91,
+        5,
+        185,
+        248,
+        192,
+        149
+      ]
+    },
+    {
+      "name": "vaultStateMigratedEvent",
+      "discriminator": [
+        55,
+        194,
+        235,
+        152,
+        21,
+        250,
+        168,
+        231
+      ]
+    },
+    {
+      "name": "withdrawClaimedEvent",
+      "discriminator": [
+        74,
+        39,
+        30,
+        156,
+        120,
+        179,
+        246,
+        222
+      ]
+    },
+    {
+      "name": "withdrawRequestedEvent",
+      "discriminator": [
+        21,
+        86,
+        249,
+        76,
+        80,
+        238,
+        207,
+        154
+      ]
+    },
+    {
+      "name": "yieldDepositedEvent",
+      "discriminator": [
+        64,
+        221,
+        209,
+        241,
+        246,
+        190,
+        243,
+        144
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "invalidExchangeRate",
+      "msg": "Invalid exchange rate"
+    },
+    {
+      "code": 6001,
+      "name": "invalidAmount",
+      "msg": "Invalid amount"
+    },
+    {
+      "code": 6002,
+      "name": "mathOverflow",
+      "msg": "Math overflow"
+    },
+    {
+      "code": 6003,
+      "name": "ticketAlreadyClaimed",
+      "msg": "Withdrawal ticket already claimed"
+    },
+    {
+      "code": 6004,
+      "name": "invalidTicketOwner",
+      "msg": "Invalid ticket owner"
+    },
+    {
+      "code": 6005,
+      "name": "withdrawalNotReady",
+      "msg": "Withdrawal not ready - unlock epoch not reached"
+    },
+    {
+      "code": 6006,
+      "name": "unauthorizedAdmin",
+      "msg": "Unauthorized - only admin can perform this action"
+    },
+    {
+      "code": 6007,
+      "name": "insufficientVaultBalance",
+      "msg": "Insufficient vault balance - vault does not have enough tokens to fulfill withdrawal"
+    },
+    {
+      "code": 6008,
+      "name": "manualRateDisabled",
+      "msg": "Manual rate changes are disabled - the exchange rate is derived from vault assets"
+    },
+    {
+      "code": 6009,
+      "name": "unauthorizedEpochAuthority",
+      "msg": "Unauthorized - only the epoch authority can advance the epoch"
+    },
+    {
+      "code": 6010,
+      "name": "epochTooEarly",
+      "msg": "Epoch advanced too early - minimum epoch duration not reached"
+    },
+    {
+      "code": 6011,
+      "name": "invalidEpochConfig",
+      "msg": "Invalid epoch configuration"
+    },
+    {
+      "code": 6012,
+      "name": "rateDecreaseNotAllowed",
+      "msg": "Exchange rate decreases must go through apply_loss"
+    },
+    {
+      "code": 6013,
+      "name": "rateIncreaseTooLarge",
+      "msg": "Exchange rate increase exceeds the per-epoch maximum"
+    },
+    {
+      "code": 6014,
+      "name": "unauthorizedLossAuthority",
+      "msg": "Unauthorized - only the loss authority can co-sign a loss"
+    },
+    {
+      "code": 6015,
+      "name": "invalidRateConfig",
+      "msg": "Invalid rate configuration"
+    },
+    {
+      "code": 6016,
+      "name": "unauthorizedRole",
+      "msg": "Unauthorized - signer does not hold the required vault role"
+    },
+    {
+      "code": 6017,
+      "name": "unauthorizedPendingAdmin",
+      "msg": "Unauthorized - only the pending admin can accept the admin transfer"
+    },
+    {
+      "code": 6018,
+      "name": "paused",
+      "msg": "Operation is paused"
+    },
+    {
+      "code": 6019,
+      "name": "invalidPauseFlags",
+      "msg": "Invalid pause flags"
+    },
+    {
+      "code": 6020,
+      "name": "invalidFeeConfig",
+      "msg": "Invalid fee configuration"
+    },
+    {
+      "code": 6021,
+      "name": "depositCapExceeded",
+      "msg": "Deposit would exceed the vault's total asset cap"
+    },
+    {
+      "code": 6022,
+      "name": "userDepositCapExceeded",
+      "msg": "Deposit would exceed the per-user deposit cap"
+    },
+    {
+      "code": 6023,
+      "name": "depositBelowMinimum",
+      "msg": "Deposit is below the vault's minimum deposit amount"
+    },
+    {
+      "code": 6024,
+      "name": "redeemBelowMinimum",
+      "msg": "Withdrawal request is below the vault's minimum redemption amount"
+    },
+    {
+      "code": 6025,
+      "name": "slippageExceeded",
+      "msg": "Output is below the caller's minimum amount"
+    },
+    {
+      "code": 6026,
+      "name": "alreadyMigrated",
+      "msg": "Vault state already uses the current layout"
+    },
+    {
+      "code": 6027,
+      "name": "invalidIouMint",
+      "msg": "IOU mint must be controlled by the vault, unfreezable, unissued and match the deposit mint's decimals"
+    },
+    {
+      "code": 6028,
+      "name": "vaultTokenAccountMismatch",
+      "msg": "Token account is not the vault's deposit token account"
+    },
+    {
+      "code": 6029,
+      "name": "depositMintMismatch",
+      "msg": "Deposit mint does not match the vault's deposit mint"
+    },
+    {
+      "code": 6030,
+      "name": "iouMintMismatch",
+      "msg": "IOU mint does not match the vault's IOU mint"
+    },
+    {
+      "code": 6031,
+      "name": "tokenAccountMintMismatch",
+      "msg": "Token account holds a different mint than the instruction expects"
+    },
+    {
+      "code": 6032,
+      "name": "tokenAccountOwnerMismatch",
+      "msg": "Token account is not owned by the signer"
+    },
+    {
+      "code": 6033,
+      "name": "treasuryOwnerMismatch",
+      "msg": "Treasury token account is not owned by the vault's treasury"
+    },
+    {
+      "code": 6034,
+      "name": "treasuryAccountMissing",
+      "msg": "Treasury token account is required to receive a fee"
+    }
+  ],
+  "types": [
+    {
+      "name": "adminAcceptedEvent",
+      "docs": [
+        "Emitted when a proposed admin accepts the transfer (preceded by a RoleUpdatedEvent for",
+        "each role that moved from the old admin)."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault handed over"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "oldAdmin",
+            "docs": [
+              "Previous admin"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "newAdmin",
+            "docs": [
+              "New admin"
+            ],
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "adminProposedEvent",
+      "docs": [
+        "Emitted when the admin proposes a new admin."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault being handed over"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "docs": [
+              "Current admin"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "pendingAdmin",
+            "docs": [
+              "Proposed admin"
+            ],
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "depositCapsUpdatedEvent",
+      "docs": [
+        "Emitted when the deposit caps change."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "maxTotalAssets",
+            "docs": [
+              "New vault-wide cap on managed assets (0 = unlimited)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "maxDepositPerUser",
+            "docs": [
+              "New per-user cap on outstanding deposits (0 = unlimited)"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "depositEvent",
+      "docs": [
+        "Emitted when a user deposits into the vault."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault deposited into"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "docs": [
+              "The depositing user"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "depositAmount",
+            "docs": [
+              "Deposit tokens paid by the user, including the entry fee"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "feeAmount",
+            "docs": [
+              "Entry fee sent to the treasury"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "iouAmount",
+            "docs": [
+              "IOU tokens minted to the user"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate the deposit was priced at"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "Epoch of the deposit"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "epochAdvancedEvent",
+      "docs": [
+        "Emitted when the vault advances to a new epoch."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault that advanced"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "The new epoch"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate at the start of the new epoch"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "slot",
+            "docs": [
+              "Slot at which the new epoch started"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "unixTimestamp",
+            "docs": [
+              "Unix timestamp at which the new epoch started"
+            ],
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "epochConfigUpdatedEvent",
+      "docs": [
+        "Emitted when the epoch configuration changes."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "minEpochSlots",
+            "docs": [
+              "New minimum number of slots per epoch"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "minEpochSeconds",
+            "docs": [
+              "New minimum number of seconds per epoch"
+            ],
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "feesUpdatedEvent",
+      "docs": [
+        "Emitted when the fee configuration changes."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "entryFeeBps",
+            "docs": [
+              "New entry fee, in basis points"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "exitFeeBps",
+            "docs": [
+              "New exit fee, in basis points"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "treasury",
+            "docs": [
+              "New treasury"
+            ],
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "legacyWithdrawClaimedEvent",
+      "docs": [
+        "Emitted when a withdrawal ticket opened by the original program is claimed and closed."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault withdrawn from"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "docs": [
+              "The claiming user"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "iouAmount",
+            "docs": [
+              "IOU tokens the ticket was opened with"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "depositAmount",
+            "docs": [
+              "Deposit tokens paid out (0 if the original program had already paid the ticket)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate at claim time"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "Epoch of the claim"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "lockWithdrawalValueUpdatedEvent",
+      "docs": [
+        "Emitted when request-time value locking is switched on or off."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "enabled",
+            "docs": [
+              "Whether new tickets now lock their value"
+            ],
+            "type": "bool"
+          }
+        ]
+      }
+    },
+    {
+      "name": "lossAppliedEvent",
+      "docs": [
+        "Emitted when apply_loss lowers the exchange rate."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault whose exchange rate was lowered"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "oldExchangeRate",
+            "docs": [
+              "Exchange rate before the loss"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "newExchangeRate",
+            "docs": [
+              "Exchange rate after the loss"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "Epoch in which the loss was applied"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "managementFeeAccruedEvent",
+      "docs": [
+        "Emitted when a management fee is paid to the treasury."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault charging the fee"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "feeShares",
+            "docs": [
+              "IOU tokens minted to the treasury"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate after the fee"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "timestamp",
+            "docs": [
+              "Unix timestamp the fee was collected up to"
+            ],
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "managementFeeUpdatedEvent",
+      "docs": [
+        "Emitted when the management fee changes."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "managementFeeBps",
+            "docs": [
+              "New annual management fee, in basis points"
+            ],
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "minimumsUpdatedEvent",
+      "docs": [
+        "Emitted when the minimum amounts change."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "minDepositAmount",
+            "docs": [
+              "New minimum deposit, in deposit tokens"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "minRedeemIouAmount",
+            "docs": [
+              "New minimum withdrawal request, in IOU tokens"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "pauseFlagsUpdatedEvent",
+      "docs": [
+        "Emitted when operations are paused or unpaused."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault whose pause flags changed"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "paused",
+            "docs": [
+              "true for pause, false for unpause"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "flags",
+            "docs": [
+              "Flags that were set or cleared"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "pausedFlags",
+            "docs": [
+              "Resulting pause bitmask"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "performanceFeeAccruedEvent",
+      "docs": [
+        "Emitted when a performance fee is paid to the treasury."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault charging the fee"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "feeShares",
+            "docs": [
+              "IOU tokens minted to the treasury"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate after the fee"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "highWaterMark",
+            "docs": [
+              "New high-water mark"
+            ],
+            "type": "u128"
+          }
+        ]
+      }
+    },
+    {
+      "name": "performanceFeeUpdatedEvent",
+      "docs": [
+        "Emitted when the performance fee changes."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "performanceFeeBps",
+            "docs": [
+              "New performance fee, in basis points"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "highWaterMark",
+            "docs": [
+              "Current high-water mark"
+            ],
+            "type": "u128"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rateConfigUpdatedEvent",
+      "docs": [
+        "Emitted when the rate configuration changes."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "maxRateIncreaseBps",
+            "docs": [
+              "New per-epoch rate increase bound, in basis points"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "lossAuthority",
+            "docs": [
+              "New loss authority"
+            ],
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rateUpdatedEvent",
+      "docs": [
+        "Emitted when increase_rate raises the exchange rate."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault whose exchange rate was raised"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "oldExchangeRate",
+            "docs": [
+              "Exchange rate before the update"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "newExchangeRate",
+            "docs": [
+              "Exchange rate after the update"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "Epoch of the update"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "roleUpdatedEvent",
+      "docs": [
+        "Emitted when a role is assigned."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "role",
+            "docs": [
+              "The assigned role"
+            ],
+            "type": {
+              "defined": {
+                "name": "vaultRole"
+              }
+            }
+          },
+          {
+            "name": "authority",
+            "docs": [
+              "Key now holding the role"
+            ],
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "shareBasedAccountingUpdatedEvent",
+      "docs": [
+        "Emitted when share-based accounting is switched on or off."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The reconfigured vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "enabled",
+            "docs": [
+              "Whether share-based accounting is now enabled"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate after re-anchoring"
+            ],
+            "type": "u128"
+          }
+        ]
+      }
     },
     {
-      "code": 6001,
-      "name": "invalidAmount",
-      "msg": "Invalid amount"
+      "name": "userPosition",
+      "docs": [
+        "UserPosition tracks a user's deposits and withdrawal tickets for a single vault.",
+        "This is a PDA derived from the user and vault_state; its counter feeds the ticket PDA seeds."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "user",
+            "docs": [
+              "The user this position belongs to"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault this position belongs to"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "nextTicketIndex",
+            "docs": [
+              "Index assigned to the user's next withdrawal ticket"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "openTickets",
+            "docs": [
+              "Number of withdrawal tickets requested but not yet claimed"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "totalDeposited",
+            "docs": [
+              "Deposit tokens invested by the user, less what the user has claimed",
+              "(counted against max_deposit_per_user)"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
     },
     {
-      "code": 6002,
-      "name": "mathOverflow",
-      "msg": "Math overflow"
+      "name": "vaultInitializedEvent",
+      "docs": [
+        "Emitted when a vault is initialized."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The new vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "docs": [
+              "Admin authority of the vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "depositMint",
+            "docs": [
+              "Mint of the deposit token"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "iouMint",
+            "docs": [
+              "Mint of the IOU token"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "vaultTokenAccount",
+            "docs": [
+              "Token account holding the vault's deposit tokens"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Initial exchange rate"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "Initial epoch"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
     },
     {
-      "code": 6003,
-      "name": "ticketAlreadyClaimed",
-      "msg": "Withdrawal ticket already claimed"
+      "name": "vaultRole",
+      "docs": [
+        "Operational roles that the admin can delegate with set_role."
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "rateManager"
+          },
+          {
+            "name": "yieldDepositor"
+          },
+          {
+            "name": "pauser"
+          },
+          {
+            "name": "feeManager"
+          },
+          {
+            "name": "epochAuthority"
+          }
+        ]
+      }
     },
     {
-      "code": 6004,
-      "name": "invalidTicketOwner",
-      "msg": "Invalid ticket owner"
+      "name": "vaultState",
+      "docs": [
+        "VaultState stores the global vault configuration and state.",
+        "This is a PDA derived from the deposit_mint to ensure one vault per deposit token type."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "admin",
+            "docs": [
+              "Admin authority that configures the vault and assigns roles"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "depositMint",
+            "docs": [
+              "The mint of tokens that can be deposited into the vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "iouMint",
+            "docs": [
+              "The mint of IOU tokens representing shares in the vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate: iou_amount = deposit_amount * EXCHANGE_RATE_SCALE / exchange_rate",
+              "When exchange_rate increases, IOU becomes more valuable (yield-bearing behavior)",
+              "Scaled by EXCHANGE_RATE_SCALE (1e18) for precision",
+              "Example: exchange_rate = 1_100_000_000_000_000_000 means 1 IOU = 1.1 tokens"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "currentEpoch",
+            "docs": [
+              "Current epoch number (incremented by the epoch authority via advance_epoch)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "shareBasedAccounting",
+            "docs": [
+              "When true, exchange_rate is derived from vault assets / outstanding IOUs",
+              "(ERC-4626 style) instead of being set by the admin"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "pendingIouAmount",
+            "docs": [
+              "IOU tokens burned into unclaimed withdrawal tickets whose value is not locked"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "lockWithdrawalValue",
+            "docs": [
+              "When true, request_withdraw snapshots the owed deposit amount into the ticket"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "pendingWithdrawalAssets",
+            "docs": [
+              "Deposit tokens owed to unclaimed tickets with a locked value (earmarked liquidity)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "epochAuthority",
+            "docs": [
+              "Role that can advance epochs"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "minEpochSlots",
+            "docs": [
+              "Minimum number of slots between epoch advances"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "minEpochSeconds",
+            "docs": [
+              "Minimum number of seconds between epoch advances"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "lastEpochSlot",
+            "docs": [
+              "Slot at which the current epoch started"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "lastEpochTimestamp",
+            "docs": [
+              "Unix timestamp at which the current epoch started"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "maxRateIncreaseBps",
+            "docs": [
+              "Maximum increase of the exchange rate per epoch, in basis points"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "epochStartRate",
+            "docs": [
+              "Exchange rate at the start of the current epoch (base for max_rate_increase_bps)"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "lossAuthority",
+            "docs": [
+              "Authority that must co-sign apply_loss (Pubkey::default() disables losses)"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "pendingAdmin",
+            "docs": [
+              "Admin proposed via propose_admin, pending acceptance (Pubkey::default() if none)"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "rateManager",
+            "docs": [
+              "Role that can raise the exchange rate and co-sign losses"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "yieldDepositor",
+            "docs": [
+              "Role that can deposit yield into the vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "pauser",
+            "docs": [
+              "Role that can pause and unpause vault operations"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "feeManager",
+            "docs": [
+              "Role that can configure vault fees"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "pausedFlags",
+            "docs": [
+              "Bitmask of paused operations (PAUSE_DEPOSIT | PAUSE_REQUEST_WITHDRAW | PAUSE_CLAIM)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "treasury",
+            "docs": [
+              "Owner of the token accounts that receive fees"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "entryFeeBps",
+            "docs": [
+              "Fee taken from each deposit, in basis points (at most MAX_FEE_BPS)"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "exitFeeBps",
+            "docs": [
+              "Fee taken from each claimed withdrawal, in basis points (at most MAX_FEE_BPS)"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "performanceFeeBps",
+            "docs": [
+              "Share of exchange rate gains above high_water_mark paid to the treasury, in basis",
+              "points (at most MAX_PERFORMANCE_FEE_BPS)"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "highWaterMark",
+            "docs": [
+              "Highest post-fee exchange rate reached so far; performance fees are only charged above it"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "managementFeeBps",
+            "docs": [
+              "Annual fee on vault value paid to the treasury, in basis points (at most",
+              "MAX_MANAGEMENT_FEE_BPS)"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "lastFeeAccrualTs",
+            "docs": [
+              "Unix timestamp up to which the management fee has been collected"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "maxTotalAssets",
+            "docs": [
+              "Maximum deposit tokens managed by the vault (0 = unlimited)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "maxDepositPerUser",
+            "docs": [
+              "Maximum outstanding deposits per user position (0 = unlimited)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "minDepositAmount",
+            "docs": [
+              "Smallest deposit accepted, in deposit tokens (0 = no minimum)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "minRedeemIouAmount",
+            "docs": [
+              "Smallest withdrawal request accepted, in IOU tokens (0 = no minimum)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "vaultTokenAccount",
+            "docs": [
+              "The vault's deposit token account (the vault_state's associated token account",
+              "for vaults created by initialize); the only account the vault holds assets in"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "Canonical bump of the vault_state PDA, stored at creation so contexts and signer",
+              "seeds do not re-derive it"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "version",
+            "docs": [
+              "Layout version (VAULT_STATE_VERSION); unversioned legacy accounts count as 0"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "reserved",
+            "docs": [
+              "Zeroed space for future fields, so adding one does not require a realloc"
+            ],
+            "type": {
+              "array": [
+                "u8",
+                128
+              ]
+            }
+          }
+        ]
+      }
     },
     {
-      "code": 6005,
-      "name": "withdrawalNotReady",
-      "msg": "Withdrawal not ready - unlock epoch not reached"
+      "name": "vaultStateMigratedEvent",
+      "docs": [
+        "Emitted when a vault state is migrated to the current layout."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The migrated vault"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "fromVersion",
+            "docs": [
+              "Layout version before the migration (0 for the unversioned legacy layout)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "toVersion",
+            "docs": [
+              "Layout version after the migration (VAULT_STATE_VERSION)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate after the migration, scaled by EXCHANGE_RATE_SCALE"
+            ],
+            "type": "u128"
+          }
+        ]
+      }
     },
     {
-      "code": 6006,
-      "name": "unauthorizedAdmin",
-      "msg": "Unauthorized - only admin can perform this action"
+      "name": "withdrawClaimedEvent",
+      "docs": [
+        "Emitted when a withdrawal ticket is claimed."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault withdrawn from"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "docs": [
+              "The claiming user"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "ticketIndex",
+            "docs": [
+              "Index of the claimed ticket"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "iouAmount",
+            "docs": [
+              "IOU tokens the ticket was opened with"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "depositAmount",
+            "docs": [
+              "Deposit tokens the ticket redeemed for, including the exit fee"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "feeAmount",
+            "docs": [
+              "Exit fee sent to the treasury"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate at claim time"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "Epoch of the claim"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
     },
     {
-      "code": 6007,
-      "name": "insufficientVaultBalance",
-      "msg": "Insufficient vault balance - vault does not have enough tokens to fulfill withdrawal"
-    }
-  ],
-  "types": [
-    {
-      "name": "vaultState",
+      "name": "withdrawRequestedEvent",
       "docs": [
-        "VaultState stores the global vault configuration and state.",
-        "This is a PDA derived from the deposit_mint to ensure one vault per deposit token type."
+        "Emitted when a user burns IOU tokens into a withdrawal ticket."
       ],
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "admin",
+            "name": "vaultState",
             "docs": [
-              "Admin authority that can update exchange rate"
+              "The vault withdrawn from"
             ],
             "type": "pubkey"
           },
           {
-            "name": "depositMint",
+            "name": "user",
             "docs": [
-              "The mint of tokens that can be deposited into the vault"
+              "The withdrawing user"
             ],
             "type": "pubkey"
           },
           {
-            "name": "iouMint",
+            "name": "ticketIndex",
             "docs": [
-              "The mint of IOU tokens representing shares in the vault"
+              "Index of the new ticket within the user's position"
             ],
-            "type": "pubkey"
+            "type": "u64"
+          },
+          {
+            "name": "iouAmount",
+            "docs": [
+              "IOU tokens burned"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "lockedDepositAmount",
+            "docs": [
+              "Deposit tokens owed if the ticket's value is locked (0 otherwise)"
+            ],
+            "type": "u64"
           },
           {
             "name": "exchangeRate",
             "docs": [
-              "Exchange rate: iou_amount = deposit_amount * EXCHANGE_RATE_SCALE / exchange_rate",
-              "When exchange_rate increases, IOU becomes more valuable (yield-bearing behavior)",
-              "Scaled by EXCHANGE_RATE_SCALE (1_000_000) for precision",
-              "Example: exchange_rate = 1_100_000 means 1 IOU = 1.1 tokens"
+              "Exchange rate at request time"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "Epoch of the request"
             ],
             "type": "u64"
           },
           {
-            "name": "currentEpoch",
+            "name": "unlockEpoch",
             "docs": [
-              "Current epoch number (incremented by admin via increase_rate)"
+              "Epoch from which the ticket can be claimed"
             ],
             "type": "u64"
           }
@@ -800,7 +4976,8 @@ export type VaultProgram = {
       "name": "withdrawalTicket",
       "docs": [
         "WithdrawalTicket represents a pending withdrawal request.",
-        "Users must wait until unlock_epoch before claiming their withdrawal."
+        "Users must wait until unlock_epoch before claiming their withdrawal.",
+        "The ticket is created by request_withdraw and closed by claim_withdraw."
       ],
       "type": {
         "kind": "struct",
@@ -812,6 +4989,13 @@ export type VaultProgram = {
             ],
             "type": "pubkey"
           },
+          {
+            "name": "ticketIndex",
+            "docs": [
+              "Index of this ticket within the user's position (used in the PDA seeds)"
+            ],
+            "type": "u64"
+          },
           {
             "name": "iouAmount",
             "docs": [
@@ -827,14 +5011,141 @@ export type VaultProgram = {
             "type": "u64"
           },
           {
-            "name": "claimed",
+            "name": "lockedDepositAmount",
             "docs": [
-              "Whether this withdrawal has been claimed"
+              "Deposit tokens owed, fixed at request time (0 when priced at the claim-time rate)"
             ],
-            "type": "bool"
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "Canonical bump of the ticket PDA, stored at creation"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "yieldDepositedEvent",
+      "docs": [
+        "Emitted when yield is deposited into the vault."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "vaultState",
+            "docs": [
+              "The vault receiving the yield"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "yieldDepositor",
+            "docs": [
+              "The yield depositor"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "yieldAmount",
+            "docs": [
+              "Deposit tokens transferred into the vault"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "exchangeRate",
+            "docs": [
+              "Exchange rate after the deposit"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "epoch",
+            "docs": [
+              "Epoch of the deposit"
+            ],
+            "type": "u64"
           }
         ]
       }
     }
+  ],
+  "constants": [
+    {
+      "name": "exchangeRateScale",
+      "docs": [
+        "Exchange rate scale factor: 1e18 means a 1:1 ratio (18 decimals of precision)"
+      ],
+      "type": "u128",
+      "value": "1000000000000000000"
+    },
+    {
+      "name": "maxFeeBps",
+      "docs": [
+        "Upper bound for the entry and exit fees, in basis points (10%)"
+      ],
+      "type": "u16",
+      "value": "1000"
+    },
+    {
+      "name": "maxManagementFeeBps",
+      "docs": [
+        "Upper bound for the annual management fee, in basis points (5% per year)"
+      ],
+      "type": "u16",
+      "value": "500"
+    },
+    {
+      "name": "maxPerformanceFeeBps",
+      "docs": [
+        "Upper bound for the performance fee, in basis points (50%)"
+      ],
+      "type": "u16",
+      "value": "5000"
+    },
+    {
+      "name": "pauseAll",
+      "docs": [
+        "All pause flags combined"
+      ],
+      "type": "u8",
+      "value": "7"
+    },
+    {
+      "name": "pauseClaim",
+      "docs": [
+        "Pause flag: blocks claim_withdraw"
+      ],
+      "type": "u8",
+      "value": "4"
+    },
+    {
+      "name": "pauseDeposit",
+      "docs": [
+        "Pause flag: blocks deposit"
+      ],
+      "type": "u8",
+      "value": "1"
+    },
+    {
+      "name": "pauseRequestWithdraw",
+      "docs": [
+        "Pause flag: blocks request_withdraw"
+      ],
+      "type": "u8",
+      "value": "2"
+    },
+    {
+      "name": "vaultStateVersion",
+      "docs": [
+        "Layout version of VaultState written by initialize and migrate_vault_state"
+      ],
+      "type": "u8",
+      "value": "1"
+    }
   ]
 };
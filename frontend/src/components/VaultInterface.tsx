@@ -9,9 +9,10 @@ import {
   increaseRate,
   depositYield,
   fetchVaultState,
-  fetchWithdrawalTicket,
+  fetchWithdrawalTickets,
   getTokenBalanceWithDecimals,
   formatExchangeRate,
+  parseExchangeRate,
   calculateIouAmount,
   calculateDepositAmount,
 } from "../lib/solana";
@@ -26,7 +27,7 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
   const program = useProgram();
   const [depositMint, setDepositMint] = useState<PublicKey | null>(null);
   const [vaultState, setVaultState] = useState<any>(null);
-  const [withdrawalTicket, setWithdrawalTicket] = useState<any>(null);
+  const [withdrawalTickets, setWithdrawalTickets] = useState<any[]>([]);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [success, setSuccess] = useState<string | null>(null);
@@ -74,33 +75,23 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
 
       if (wallet.publicKey) {
         try {
-          const ticket = await fetchWithdrawalTicket(
+          const tickets = await fetchWithdrawalTickets(
             program,
             mint,
             wallet.publicKey
           );
-          setWithdrawalTicket(ticket);
+          setWithdrawalTickets(tickets);
         } catch (ticketError: any) {
-          // Withdrawal ticket errors are non-fatal - user might not have one yet
-          // Only log if it's not an "account not found" error (which is expected)
-          const errorMsg = ticketError?.message || String(ticketError) || "";
-          if (
-            !errorMsg.includes("does not exist") &&
-            !errorMsg.includes("has no data")
-          ) {
-            console.warn(
-              "Unexpected error fetching withdrawal ticket:",
-              ticketError
-            );
-          }
-          setWithdrawalTicket(null);
+          // Withdrawal ticket errors are non-fatal - the UI just shows no tickets
+          console.warn("Unexpected error fetching withdrawal tickets:", ticketError);
+          setWithdrawalTickets([]);
         }
       }
     } catch (err: any) {
       console.error("Error loading vault data:", err);
       setError(err.message || "Failed to load vault data");
       setVaultState(null);
-      setWithdrawalTicket(null);
+      setWithdrawalTickets([]);
     }
   };
 
@@ -207,7 +198,7 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
     }
   };
 
-  const handleClaimWithdraw = async () => {
+  const handleClaimWithdraw = async (ticketIndex: number) => {
     if (!wallet.publicKey || !depositMint) {
       setError("Please connect your wallet");
       return;
@@ -218,7 +209,7 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
     setSuccess(null);
 
     try {
-      const signature = await claimWithdraw(program, depositMint);
+      const signature = await claimWithdraw(program, depositMint, ticketIndex);
       setSuccess(`Withdrawal claimed! Signature: ${signature}`);
       await loadUserBalances();
       await loadVaultData(depositMint);
@@ -235,8 +226,13 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
       return;
     }
 
-    const rate = parseFloat(newExchangeRate);
-    if (isNaN(rate) || rate <= 0) {
+    let scaledRate;
+    try {
+      scaledRate = parseExchangeRate(newExchangeRate);
+    } catch {
+      scaledRate = null;
+    }
+    if (!scaledRate || scaledRate.isZero()) {
       setError("Please enter a valid exchange rate");
       return;
     }
@@ -246,8 +242,6 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
     setSuccess(null);
 
     try {
-      // Convert to scaled rate (multiply by 1,000,000)
-      const scaledRate = Math.floor(rate * 1_000_000);
       const signature = await increaseRate(program, depositMint, scaledRate);
       setSuccess(`Exchange rate increased! Signature: ${signature}`);
       setNewExchangeRate("");
@@ -291,16 +285,18 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
     }
   };
 
-  const isAdmin =
+  const isRateManager =
     wallet.publicKey &&
     vaultState &&
-    wallet.publicKey.toString() === vaultState.admin.toString();
+    wallet.publicKey.toString() === vaultState.rateManager.toString();
 
-  const canClaim =
-    withdrawalTicket &&
-    !withdrawalTicket.claimed &&
+  const isYieldDepositor =
+    wallet.publicKey &&
     vaultState &&
-    vaultState.currentEpoch >= withdrawalTicket.unlockEpoch;
+    wallet.publicKey.toString() === vaultState.yieldDepositor.toString();
+
+  const canClaim = (ticket: any) =>
+    vaultState && vaultState.currentEpoch.gte(ticket.unlockEpoch);
 
   return (
     <div className="vault-interface">
@@ -326,7 +322,7 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
           <h3>Vault Information</h3>
           <p>
             <strong>Exchange Rate:</strong>{" "}
-            {formatExchangeRate(vaultState.exchangeRate.toNumber())}
+            {formatExchangeRate(vaultState.exchangeRate)}
           </p>
           <p>
             <strong>Current Epoch:</strong> {vaultState.currentEpoch.toString()}
@@ -340,6 +336,36 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
           <p>
             <strong>Admin:</strong> {vaultState.admin.toString()}
           </p>
+          <p>
+            <strong>Fees:</strong> entry {vaultState.entryFeeBps / 100}%, exit{" "}
+            {vaultState.exitFeeBps / 100}%, performance{" "}
+            {vaultState.performanceFeeBps / 100}%, management{" "}
+            {vaultState.managementFeeBps / 100}%/year
+          </p>
+          {(!vaultState.minDepositAmount.isZero() ||
+            !vaultState.minRedeemIouAmount.isZero()) && (
+            <p>
+              <strong>Minimums:</strong> deposit{" "}
+              {vaultState.minDepositAmount.toNumber() /
+                Math.pow(10, depositDecimals)}
+              , withdrawal request{" "}
+              {vaultState.minRedeemIouAmount.toNumber() /
+                Math.pow(10, iouDecimals)}{" "}
+              IOU
+            </p>
+          )}
+          {vaultState.pausedFlags !== 0 && (
+            <p style={{ color: "orange" }}>
+              <strong>Paused:</strong>{" "}
+              {[
+                vaultState.pausedFlags & 1 ? "deposits" : null,
+                vaultState.pausedFlags & 2 ? "withdrawal requests" : null,
+                vaultState.pausedFlags & 4 ? "claims" : null,
+              ]
+                .filter(Boolean)
+                .join(", ")}
+            </p>
+          )}
         </div>
       )}
 
@@ -355,43 +381,54 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
         </div>
       )}
 
-      {withdrawalTicket && !withdrawalTicket.claimed && (
+      {withdrawalTickets.length > 0 && (
         <div className="withdrawal-ticket" style={{ marginBottom: "2rem" }}>
-          <h3>Pending Withdrawal</h3>
-          <p>
-            <strong>IOU Amount:</strong>{" "}
-            {(
-              withdrawalTicket.iouAmount.toNumber() / Math.pow(10, iouDecimals)
-            ).toFixed(6)}
-          </p>
-          <p>
-            <strong>Unlock Epoch:</strong>{" "}
-            {withdrawalTicket.unlockEpoch.toString()}
-          </p>
+          <h3>Pending Withdrawals</h3>
           <p>
             <strong>Current Epoch:</strong>{" "}
             {vaultState?.currentEpoch.toString() || "Loading..."}
           </p>
-          {canClaim ? (
-            <button
-              onClick={handleClaimWithdraw}
-              disabled={loading}
-              style={{
-                padding: "0.5rem 1rem",
-                backgroundColor: "#4CAF50",
-                color: "white",
-                border: "none",
-                borderRadius: "4px",
-                cursor: loading ? "not-allowed" : "pointer",
-              }}
+          {withdrawalTickets.map((ticket) => (
+            <div
+              key={ticket.ticketIndex.toString()}
+              style={{ marginBottom: "1rem" }}
             >
-              {loading ? "Claiming..." : "Claim Withdrawal"}
-            </button>
-          ) : (
-            <p style={{ color: "orange" }}>
-              Waiting for unlock epoch to be reached...
-            </p>
-          )}
+              <p>
+                <strong>Ticket #{ticket.ticketIndex.toString()}:</strong>{" "}
+                {(
+                  ticket.iouAmount.toNumber() / Math.pow(10, iouDecimals)
+                ).toFixed(6)}{" "}
+                IOU, unlocks at epoch {ticket.unlockEpoch.toString()}
+                {ticket.lockedDepositAmount.gtn(0) &&
+                  ` (locked at ${(
+                    ticket.lockedDepositAmount.toNumber() /
+                    Math.pow(10, depositDecimals)
+                  ).toFixed(6)} tokens)`}
+              </p>
+              {canClaim(ticket) ? (
+                <button
+                  onClick={() =>
+                    handleClaimWithdraw(ticket.ticketIndex.toNumber())
+                  }
+                  disabled={loading}
+                  style={{
+                    padding: "0.5rem 1rem",
+                    backgroundColor: "#4CAF50",
+                    color: "white",
+                    border: "none",
+                    borderRadius: "4px",
+                    cursor: loading ? "not-allowed" : "pointer",
+                  }}
+                >
+                  {loading ? "Claiming..." : "Claim Withdrawal"}
+                </button>
+              ) : (
+                <p style={{ color: "orange" }}>
+                  Waiting for unlock epoch to be reached...
+                </p>
+              )}
+            </div>
+          ))}
         </div>
       )}
 
@@ -434,7 +471,7 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
                 {(
                   calculateIouAmount(
                     parseFloat(depositAmount) * Math.pow(10, depositDecimals),
-                    vaultState.exchangeRate.toNumber()
+                    vaultState.exchangeRate
                   ) / Math.pow(10, iouDecimals)
                 ).toFixed(6)}{" "}
                 IOU tokens
@@ -477,7 +514,7 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
                 {(
                   calculateDepositAmount(
                     parseFloat(withdrawAmount) * Math.pow(10, iouDecimals),
-                    vaultState.exchangeRate.toNumber()
+                    vaultState.exchangeRate
                   ) / Math.pow(10, depositDecimals)
                 ).toFixed(6)}{" "}
                 deposit tokens (at current rate)
@@ -485,63 +522,64 @@ export function VaultInterface({ depositMintAddress }: VaultInterfaceProps) {
             )}
           </div>
 
-          {isAdmin && (
-            <>
-              <div style={{ marginBottom: "1rem" }}>
-                <h4>Increase Exchange Rate (Admin Only)</h4>
-                <input
-                  type="number"
-                  value={newExchangeRate}
-                  onChange={(e) => setNewExchangeRate(e.target.value)}
-                  placeholder="New exchange rate (e.g., 1.1 for 10% increase)"
-                  step="0.01"
-                  style={{ marginRight: "0.5rem", padding: "0.5rem" }}
-                />
-                <button
-                  onClick={handleIncreaseRate}
-                  disabled={loading}
-                  style={{
-                    padding: "0.5rem 1rem",
-                    backgroundColor: "#9C27B0",
-                    color: "white",
-                    border: "none",
-                    borderRadius: "4px",
-                    cursor: loading ? "not-allowed" : "pointer",
-                  }}
-                >
-                  {loading ? "Processing..." : "Increase Rate"}
-                </button>
-              </div>
-              <div style={{ marginBottom: "1rem" }}>
-                <h4>Deposit Yield (Admin Only)</h4>
-                <p style={{ fontSize: "0.9rem", color: "gray", marginBottom: "0.5rem" }}>
-                  Deposit tokens into the vault without minting IOU tokens.
-                  This represents yield/staking rewards that benefit existing holders.
-                </p>
-                <input
-                  type="number"
-                  value={yieldAmount}
-                  onChange={(e) => setYieldAmount(e.target.value)}
-                  placeholder="Yield amount to deposit"
-                  step="0.01"
-                  style={{ marginRight: "0.5rem", padding: "0.5rem" }}
-                />
-                <button
-                  onClick={handleDepositYield}
-                  disabled={loading}
-                  style={{
-                    padding: "0.5rem 1rem",
-                    backgroundColor: "#4CAF50",
-                    color: "white",
-                    border: "none",
-                    borderRadius: "4px",
-                    cursor: loading ? "not-allowed" : "pointer",
-                  }}
-                >
-                  {loading ? "Processing..." : "Deposit Yield"}
-                </button>
-              </div>
-            </>
+          {isRateManager && (
+            <div style={{ marginBottom: "1rem" }}>
+              <h4>Increase Exchange Rate (Rate Manager Only)</h4>
+              <input
+                type="number"
+                value={newExchangeRate}
+                onChange={(e) => setNewExchangeRate(e.target.value)}
+                placeholder="New exchange rate (e.g., 1.1 for 10% increase)"
+                step="0.01"
+                style={{ marginRight: "0.5rem", padding: "0.5rem" }}
+              />
+              <button
+                onClick={handleIncreaseRate}
+                disabled={loading}
+                style={{
+                  padding: "0.5rem 1rem",
+                  backgroundColor: "#9C27B0",
+                  color: "white",
+                  border: "none",
+                  borderRadius: "4px",
+                  cursor: loading ? "not-allowed" : "pointer",
+                }}
+              >
+                {loading ? "Processing..." : "Increase Rate"}
+              </button>
+            </div>
+          )}
+
+          {isYieldDepositor && (
+            <div style={{ marginBottom: "1rem" }}>
+              <h4>Deposit Yield (Yield Depositor Only)</h4>
+              <p style={{ fontSize: "0.9rem", color: "gray", marginBottom: "0.5rem" }}>
+                Deposit tokens into the vault without minting IOU tokens.
+                This represents yield/staking rewards that benefit existing holders.
+              </p>
+              <input
+                type="number"
+                value={yieldAmount}
+                onChange={(e) => setYieldAmount(e.target.value)}
+                placeholder="Yield amount to deposit"
+                step="0.01"
+                style={{ marginRight: "0.5rem", padding: "0.5rem" }}
+              />
+              <button
+                onClick={handleDepositYield}
+                disabled={loading}
+                style={{
+                  padding: "0.5rem 1rem",
+                  backgroundColor: "#4CAF50",
+                  color: "white",
+                  border: "none",
+                  borderRadius: "4px",
+                  cursor: loading ? "not-allowed" : "pointer",
+                }}
+              >
+                {loading ? "Processing..." : "Deposit Yield"}
+              </button>
+            </div>
           )}
         </div>
       )}
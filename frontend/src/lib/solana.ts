@@ -7,7 +7,6 @@ import {
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
-  ASSOCIATED_TOKEN_PROGRAM_ID,
   getAssociatedTokenAddress,
   createAssociatedTokenAccountInstruction,
   getAccount,
@@ -24,8 +23,9 @@ const PROGRAM_ID = new PublicKey(
   "D7KrGPhkyWsqMRS7kQjaGzyT48nTaw4AopWM6qXXmBtg"
 );
 
-// Exchange rate scale factor (matches on-chain constant)
-const EXCHANGE_RATE_SCALE = 1_000_000;
+// Exchange rate scale factor (matches on-chain constant, 1e18)
+const EXCHANGE_RATE_DECIMALS = 18;
+const EXCHANGE_RATE_SCALE = new BN(10).pow(new BN(EXCHANGE_RATE_DECIMALS));
 
 /**
  * Hook to get the Anchor program instance.
@@ -75,14 +75,33 @@ export function getVaultStatePda(depositMint: PublicKey): [PublicKey, number] {
 }
 
 /**
- * Derive the withdrawal_ticket PDA from user and vault_state
+ * Derive the user_position PDA from user and vault_state
  */
-export function getWithdrawalTicketPda(
+export function getUserPositionPda(
   user: PublicKey,
   vaultState: PublicKey
 ): [PublicKey, number] {
   return PublicKey.findProgramAddressSync(
-    [Buffer.from("withdrawal_ticket"), user.toBuffer(), vaultState.toBuffer()],
+    [Buffer.from("user_position"), user.toBuffer(), vaultState.toBuffer()],
+    PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the withdrawal_ticket PDA from user, vault_state and ticket index
+ */
+export function getWithdrawalTicketPda(
+  user: PublicKey,
+  vaultState: PublicKey,
+  ticketIndex: number
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("withdrawal_ticket"),
+      user.toBuffer(),
+      vaultState.toBuffer(),
+      new BN(ticketIndex).toArrayLike(Buffer, "le", 8),
+    ],
     PROGRAM_ID
   );
 }
@@ -104,6 +123,24 @@ export async function getTokenAccountAddress(
   );
 }
 
+/**
+ * Get the treasury's associated token account for a mint, or null if the treasury has not
+ * created it. User instructions only need the treasury's accounts when a fee is charged.
+ */
+export async function getTreasuryTokenAccount(
+  connection: Connection,
+  mint: PublicKey,
+  treasury: PublicKey
+): Promise<PublicKey | null> {
+  const tokenAccount = await getTokenAccountAddress(
+    mint,
+    treasury,
+    true // Treasury may be a PDA
+  );
+  const accountInfo = await connection.getAccountInfo(tokenAccount, "confirmed");
+  return accountInfo ? tokenAccount : null;
+}
+
 /**
  * Ensure a token account exists, creating it if necessary
  */
@@ -130,11 +167,13 @@ export async function ensureTokenAccount(
 /**
  * Deposit tokens into the vault and receive IOU tokens
  * Uses the program instance from useProgram() hook (following official Anchor pattern)
+ * The transaction reverts if fewer than minIouOut IOU tokens would be minted (0 = no check)
  */
 export async function deposit(
   program: Program<VaultProgram>,
   depositMint: PublicKey,
-  depositAmount: number
+  depositAmount: number,
+  minIouOut: number = 0
 ): Promise<string> {
   const connection = program.provider.connection;
   const wallet = program.provider.wallet;
@@ -156,64 +195,23 @@ export async function deposit(
     wallet.publicKey,
     false // User wallet is on-curve
   );
-  const vaultDepositTokenAccount = await getTokenAccountAddress(
-    depositMint,
-    vaultStatePda,
-    true // Vault PDA is off-curve
-  );
+  // The vault's deposit token account is created at initialize and recorded on-chain
+  const vaultDepositTokenAccount = vaultState.vaultTokenAccount as PublicKey;
   const userIouTokenAccount = await getTokenAccountAddress(
     iouMint,
     wallet.publicKey,
     false // User wallet is on-curve
   );
-
-  // Ensure vault deposit token account exists
-  // This account is owned by the vault PDA (off-curve), so we need to create it
-  // if it doesn't exist. The user will pay for the account creation.
-  try {
-    await getAccount(
-      connection,
-      vaultDepositTokenAccount,
-      "confirmed",
-      TOKEN_PROGRAM_ID
-    );
-  } catch {
-    // Account doesn't exist, create it
-    // For off-curve owners (PDA), we need to create the associated token account
-    // The createAssociatedTokenAccountInstruction supports off-curve owners
-    // when using the associated token program
-    const createIx = createAssociatedTokenAccountInstruction(
-      wallet.publicKey, // payer
-      vaultDepositTokenAccount, // associated token account address
-      vaultStatePda, // owner (PDA, off-curve) - this is allowed
-      depositMint,
-      TOKEN_PROGRAM_ID,
-      ASSOCIATED_TOKEN_PROGRAM_ID
-    );
-
-    // Build and send transaction
-    const tx = new Transaction().add(createIx);
-    const { blockhash, lastValidBlockHeight } =
-      await connection.getLatestBlockhash("confirmed");
-    tx.recentBlockhash = blockhash;
-    tx.feePayer = wallet.publicKey;
-
-    // Sign and send
-    const signed = await wallet.signTransaction(tx);
-    const sig = await connection.sendRawTransaction(signed.serialize(), {
-      skipPreflight: false,
-    });
-
-    // Wait for confirmation
-    await connection.confirmTransaction(
-      {
-        signature: sig,
-        blockhash,
-        lastValidBlockHeight,
-      },
-      "confirmed"
-    );
-  }
+  const treasuryDepositTokenAccount = await getTreasuryTokenAccount(
+    connection,
+    depositMint,
+    vaultState.treasury as PublicKey
+  );
+  const treasuryIouTokenAccount = await getTreasuryTokenAccount(
+    connection,
+    iouMint,
+    vaultState.treasury as PublicKey
+  );
 
   // Ensure user token accounts exist
   try {
@@ -269,7 +267,7 @@ export async function deposit(
   // Send deposit transaction
   // Anchor will auto-resolve vaultState PDA from depositMint, but we pass it explicitly for clarity
   const signature = await program.methods
-    .deposit(new BN(depositAmount))
+    .deposit(new BN(depositAmount), new BN(minIouOut))
     .accounts({
       user: wallet.publicKey,
       vaultState: vaultStatePda,
@@ -278,7 +276,11 @@ export async function deposit(
       userDepositTokenAccount: userDepositTokenAccount,
       vaultDepositTokenAccount: vaultDepositTokenAccount,
       userIouTokenAccount: userIouTokenAccount,
+      treasuryDepositTokenAccount: treasuryDepositTokenAccount,
+      treasuryIouTokenAccount: treasuryIouTokenAccount,
+      userPosition: getUserPositionPda(wallet.publicKey, vaultStatePda)[0],
       tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
     } as any)
     .rpc();
 
@@ -307,15 +309,30 @@ export async function requestWithdraw(
   const vaultState = await program.account.vaultState.fetch(vaultStatePda);
   const iouMint = vaultState.iouMint as PublicKey;
 
-  // Get token accounts and withdrawal ticket
+  // Get token accounts and the next withdrawal ticket
   const userIouTokenAccount = await getTokenAccountAddress(
     iouMint,
     wallet.publicKey,
     false // User wallet is on-curve
   );
+  // The vault's deposit token account is created at initialize and recorded on-chain
+  const vaultDepositTokenAccount = vaultState.vaultTokenAccount as PublicKey;
+  const treasuryIouTokenAccount = await getTreasuryTokenAccount(
+    program.provider.connection,
+    iouMint,
+    vaultState.treasury as PublicKey
+  );
+  const [userPositionPda] = getUserPositionPda(wallet.publicKey, vaultStatePda);
+  const userPosition = await program.account.userPosition.fetchNullable(
+    userPositionPda
+  );
+  const nextTicketIndex = userPosition
+    ? userPosition.nextTicketIndex.toNumber()
+    : 0;
   const [withdrawalTicketPda] = getWithdrawalTicketPda(
     wallet.publicKey,
-    vaultStatePda
+    vaultStatePda,
+    nextTicketIndex
   );
 
   // Send transaction
@@ -326,6 +343,9 @@ export async function requestWithdraw(
       vaultState: vaultStatePda,
       iouMint: iouMint,
       userIouTokenAccount: userIouTokenAccount,
+      vaultDepositTokenAccount: vaultDepositTokenAccount,
+      treasuryIouTokenAccount: treasuryIouTokenAccount,
+      userPosition: userPositionPda,
       withdrawalTicket: withdrawalTicketPda,
       tokenProgram: TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
@@ -336,12 +356,15 @@ export async function requestWithdraw(
 }
 
 /**
- * Claim withdrawal after unlock epoch
+ * Claim a withdrawal ticket after its unlock epoch
  * Uses the program instance from useProgram() hook (following official Anchor pattern)
+ * The transaction reverts if fewer than minAssetsOut deposit tokens would be paid (0 = no check)
  */
 export async function claimWithdraw(
   program: Program<VaultProgram>,
-  depositMint: PublicKey
+  depositMint: PublicKey,
+  ticketIndex: number,
+  minAssetsOut: number = 0
 ): Promise<string> {
   const connection = program.provider.connection;
   const wallet = program.provider.wallet;
@@ -353,20 +376,33 @@ export async function claimWithdraw(
   // Derive vault_state PDA
   const [vaultStatePda] = getVaultStatePda(depositMint);
 
+  // Fetch vault state to get IOU mint
+  const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+  const iouMint = vaultState.iouMint as PublicKey;
+
   // Get token accounts and withdrawal ticket
   const userDepositTokenAccount = await getTokenAccountAddress(
     depositMint,
     wallet.publicKey,
     false // User wallet is on-curve
   );
-  const vaultDepositTokenAccount = await getTokenAccountAddress(
+  // The vault's deposit token account is created at initialize and recorded on-chain
+  const vaultDepositTokenAccount = vaultState.vaultTokenAccount as PublicKey;
+  const treasuryDepositTokenAccount = await getTreasuryTokenAccount(
+    connection,
     depositMint,
-    vaultStatePda,
-    true // Vault PDA is off-curve
+    vaultState.treasury as PublicKey
+  );
+  const treasuryIouTokenAccount = await getTreasuryTokenAccount(
+    connection,
+    iouMint,
+    vaultState.treasury as PublicKey
   );
+  const [userPositionPda] = getUserPositionPda(wallet.publicKey, vaultStatePda);
   const [withdrawalTicketPda] = getWithdrawalTicketPda(
     wallet.publicKey,
-    vaultStatePda
+    vaultStatePda,
+    ticketIndex
   );
 
   // Ensure user deposit token account exists
@@ -397,13 +433,17 @@ export async function claimWithdraw(
 
   // Send claim transaction
   const signature = await program.methods
-    .claimWithdraw()
+    .claimWithdraw(new BN(minAssetsOut))
     .accounts({
       user: wallet.publicKey,
       vaultState: vaultStatePda,
       depositMint: depositMint,
+      iouMint: iouMint,
       vaultDepositTokenAccount: vaultDepositTokenAccount,
       userDepositTokenAccount: userDepositTokenAccount,
+      treasuryDepositTokenAccount: treasuryDepositTokenAccount,
+      treasuryIouTokenAccount: treasuryIouTokenAccount,
+      userPosition: userPositionPda,
       withdrawalTicket: withdrawalTicketPda,
       tokenProgram: TOKEN_PROGRAM_ID,
     } as any)
@@ -413,13 +453,13 @@ export async function claimWithdraw(
 }
 
 /**
- * Increase exchange rate (admin-only)
+ * Increase exchange rate (rate-manager-only)
  * Uses the program instance from useProgram() hook (following official Anchor pattern)
  */
 export async function increaseRate(
   program: Program<VaultProgram>,
   depositMint: PublicKey,
-  newExchangeRate: number
+  newExchangeRate: BN
 ): Promise<string> {
   const wallet = program.provider.wallet;
 
@@ -430,21 +470,33 @@ export async function increaseRate(
   // Derive vault_state PDA
   const [vaultStatePda] = getVaultStatePda(depositMint);
 
+  // Fetch vault state to get IOU mint and treasury (performance fees are minted to it)
+  const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+  const iouMint = vaultState.iouMint as PublicKey;
+  const treasuryIouTokenAccount = await getTokenAccountAddress(
+    iouMint,
+    vaultState.treasury as PublicKey,
+    true // Treasury may be a PDA
+  );
+
   // Send transaction
   const signature = await program.methods
-    .increaseRate(new BN(newExchangeRate))
+    .increaseRate(newExchangeRate)
     .accounts({
-      admin: wallet.publicKey,
+      rateManager: wallet.publicKey,
       vaultState: vaultStatePda,
-    })
+      iouMint: iouMint,
+      treasuryIouTokenAccount: treasuryIouTokenAccount,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    } as any)
     .rpc();
 
   return signature;
 }
 
 /**
- * Deposit yield tokens into the vault (admin-only)
- * Transfers tokens from admin to vault without minting IOU tokens
+ * Deposit yield tokens into the vault (yield-depositor-only)
+ * Transfers tokens from the yield depositor to vault without minting IOU tokens
  * This represents yield/staking rewards that benefit existing holders
  */
 export async function depositYield(
@@ -462,23 +514,29 @@ export async function depositYield(
   // Derive vault_state PDA
   const [vaultStatePda] = getVaultStatePda(depositMint);
 
+  // Fetch vault state to get IOU mint
+  const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+  const iouMint = vaultState.iouMint as PublicKey;
+
   // Get token accounts
-  const adminDepositTokenAccount = await getTokenAccountAddress(
+  const yieldDepositorTokenAccount = await getTokenAccountAddress(
     depositMint,
     wallet.publicKey,
-    false // Admin wallet is on-curve
+    false // Yield depositor wallet is on-curve
   );
-  const vaultDepositTokenAccount = await getTokenAccountAddress(
-    depositMint,
-    vaultStatePda,
-    true // Vault PDA is off-curve
+  // The vault's deposit token account is created at initialize and recorded on-chain
+  const vaultDepositTokenAccount = vaultState.vaultTokenAccount as PublicKey;
+  const treasuryIouTokenAccount = await getTokenAccountAddress(
+    iouMint,
+    vaultState.treasury as PublicKey,
+    true // Treasury may be a PDA
   );
 
-  // Ensure admin token account exists
+  // Ensure yield depositor token account exists
   try {
     await getAccount(
       connection,
-      adminDepositTokenAccount,
+      yieldDepositorTokenAccount,
       "confirmed",
       TOKEN_PROGRAM_ID
     );
@@ -486,7 +544,7 @@ export async function depositYield(
     // Account doesn't exist, create it
     const createIx = createAssociatedTokenAccountInstruction(
       wallet.publicKey,
-      adminDepositTokenAccount,
+      yieldDepositorTokenAccount,
       wallet.publicKey,
       depositMint,
       TOKEN_PROGRAM_ID
@@ -500,52 +558,17 @@ export async function depositYield(
     await connection.confirmTransaction(sig, "confirmed");
   }
 
-  // Ensure vault deposit token account exists
-  try {
-    await getAccount(
-      connection,
-      vaultDepositTokenAccount,
-      "confirmed",
-      TOKEN_PROGRAM_ID
-    );
-  } catch {
-    // Account doesn't exist, create it
-    const createIx = createAssociatedTokenAccountInstruction(
-      wallet.publicKey,
-      vaultDepositTokenAccount,
-      vaultStatePda,
-      depositMint,
-      TOKEN_PROGRAM_ID,
-      ASSOCIATED_TOKEN_PROGRAM_ID
-    );
-    const tx = new Transaction().add(createIx);
-    const { blockhash, lastValidBlockHeight } =
-      await connection.getLatestBlockhash("confirmed");
-    tx.recentBlockhash = blockhash;
-    tx.feePayer = wallet.publicKey;
-    const signed = await wallet.signTransaction(tx);
-    const sig = await connection.sendRawTransaction(signed.serialize(), {
-      skipPreflight: false,
-    });
-    await connection.confirmTransaction(
-      {
-        signature: sig,
-        blockhash,
-        lastValidBlockHeight,
-      },
-      "confirmed"
-    );
-  }
-
   // Send deposit yield transaction
   const signature = await program.methods
     .depositYield(new BN(yieldAmount))
     .accounts({
-      admin: wallet.publicKey,
+      yieldDepositor: wallet.publicKey,
       vaultState: vaultStatePda,
       depositMint: depositMint,
-      adminDepositTokenAccount: adminDepositTokenAccount,
+      iouMint: iouMint,
+      yieldDepositorTokenAccount: yieldDepositorTokenAccount,
       vaultDepositTokenAccount: vaultDepositTokenAccount,
+      treasuryIouTokenAccount: treasuryIouTokenAccount,
       tokenProgram: TOKEN_PROGRAM_ID,
     } as any)
     .rpc();
@@ -593,10 +616,10 @@ export async function fetchVaultState(
 }
 
 /**
- * Fetch withdrawal ticket for a user
+ * Fetch all pending withdrawal tickets for a user
  * Can use either a program instance or connection (for read-only access)
  */
-export async function fetchWithdrawalTicket(
+export async function fetchWithdrawalTickets(
   programOrConnection: Program<VaultProgram> | Connection,
   depositMint: PublicKey,
   user: PublicKey
@@ -606,48 +629,29 @@ export async function fetchWithdrawalTicket(
       ? getReadOnlyProgram(programOrConnection)
       : programOrConnection;
   const [vaultStatePda] = getVaultStatePda(depositMint);
-  const [withdrawalTicketPda] = getWithdrawalTicketPda(user, vaultStatePda);
-
-  try {
-    return await program.account.withdrawalTicket.fetch(withdrawalTicketPda);
-  } catch (error: any) {
-    // Check for various error codes/messages that indicate account doesn't exist
-    // This is normal - users won't have a withdrawal ticket until they request one
-
-    // Get error message from various possible locations
-    const errorMessage =
-      error?.message || error?.toString() || String(error) || "";
+  const [userPositionPda] = getUserPositionPda(user, vaultStatePda);
 
-    // Get error code from various possible locations
-    const errorCode =
-      error?.code ||
-      error?.errorCode ||
-      error?.error?.code ||
-      error?.errorCodeNumber;
+  // Users won't have a position until their first withdrawal request
+  const userPosition = await program.account.userPosition.fetchNullable(
+    userPositionPda
+  );
+  if (!userPosition) {
+    return [];
+  }
 
-    // Check if this is an "account doesn't exist" error
-    // Anchor throws errors with message "Account does not exist or has no data"
-    const isAccountNotFoundError =
-      errorCode === "AccountDoesNotExist" ||
-      errorCode === 301 ||
-      errorCode === "AccountNotFound" ||
-      errorMessage.toLowerCase().includes("does not exist") ||
-      errorMessage.toLowerCase().includes("account does not exist") ||
-      errorMessage.toLowerCase().includes("has no data") ||
-      errorMessage
-        .toLowerCase()
-        .includes("account does not exist or has no data") ||
-      errorMessage.toLowerCase().includes("invalid account data");
-
-    if (isAccountNotFoundError) {
-      // This is expected - user hasn't created a withdrawal ticket yet
-      return null;
-    }
+  // Ticket PDAs are indexed 0..next_ticket_index; claimed tickets are closed,
+  // so only pending ones come back from the batch fetch
+  const ticketPdas = Array.from(
+    { length: userPosition.nextTicketIndex.toNumber() },
+    (_, ticketIndex) =>
+      getWithdrawalTicketPda(user, vaultStatePda, ticketIndex)[0]
+  );
+  const tickets =
+    await program.account.withdrawalTicket.fetchMultiple(ticketPdas);
 
-    // If it's not an account not found error, re-throw it
-    console.error("Unexpected error fetching withdrawal ticket:", error);
-    throw error;
-  }
+  return tickets.filter(
+    (ticket): ticket is NonNullable<typeof ticket> => ticket !== null
+  );
 }
 
 /**
@@ -708,10 +712,33 @@ export async function getTokenBalanceWithDecimals(
 }
 
 /**
- * Format exchange rate for display
+ * Format exchange rate for display (9 of its 18 decimals)
+ */
+export function formatExchangeRate(exchangeRate: BN): string {
+  const whole = exchangeRate.div(EXCHANGE_RATE_SCALE).toString();
+  const fraction = exchangeRate
+    .mod(EXCHANGE_RATE_SCALE)
+    .toString()
+    .padStart(EXCHANGE_RATE_DECIMALS, "0")
+    .slice(0, 9);
+  return `${whole}.${fraction}`;
+}
+
+/**
+ * Parse a decimal exchange rate (e.g. "1.1") into its on-chain scaled value
  */
-export function formatExchangeRate(exchangeRate: number): string {
-  return (exchangeRate / EXCHANGE_RATE_SCALE).toFixed(6);
+export function parseExchangeRate(rate: string): BN {
+  const match = /^(\d*)(?:\.(\d*))?$/.exec(rate.trim());
+  if (!match || (!match[1] && !match[2])) {
+    throw new Error(`Invalid exchange rate: ${rate}`);
+  }
+  const [, whole, fraction = ""] = match;
+  const scaledFraction = fraction
+    .slice(0, EXCHANGE_RATE_DECIMALS)
+    .padEnd(EXCHANGE_RATE_DECIMALS, "0");
+  return new BN(whole || "0")
+    .mul(EXCHANGE_RATE_SCALE)
+    .add(new BN(scaledFraction));
 }
 
 /**
@@ -719,9 +746,12 @@ export function formatExchangeRate(exchangeRate: number): string {
  */
 export function calculateIouAmount(
   depositAmount: number,
-  exchangeRate: number
+  exchangeRate: BN
 ): number {
-  return Math.floor((depositAmount * EXCHANGE_RATE_SCALE) / exchangeRate);
+  return new BN(Math.floor(depositAmount))
+    .mul(EXCHANGE_RATE_SCALE)
+    .div(exchangeRate)
+    .toNumber();
 }
 
 /**
@@ -729,7 +759,10 @@ export function calculateIouAmount(
  */
 export function calculateDepositAmount(
   iouAmount: number,
-  exchangeRate: number
+  exchangeRate: BN
 ): number {
-  return Math.floor((iouAmount * exchangeRate) / EXCHANGE_RATE_SCALE);
+  return new BN(Math.floor(iouAmount))
+    .mul(exchangeRate)
+    .div(EXCHANGE_RATE_SCALE)
+    .toNumber();
 }
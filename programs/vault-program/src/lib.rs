@@ -1,12 +1,75 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{
     self, BurnChecked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
 };
 
+mod math;
+
+use math::{
+    assets_to_ious_ceil, assets_to_ious_floor, ious_to_assets_ceil, ious_to_assets_floor,
+    mul_div_ceil, mul_div_floor, mul_div_floor_u128, share_rate, to_u64, Rounding,
+};
+
 declare_id!("D7KrGPhkyWsqMRS7kQjaGzyT48nTaw4AopWM6qXXmBtg");
 
-// Exchange rate scale factor: 1_000_000 means 1:1 ratio (with 6 decimals precision)
-const EXCHANGE_RATE_SCALE: u64 = 1_000_000;
+/// Exchange rate scale factor: 1e18 means a 1:1 ratio (18 decimals of precision)
+#[constant]
+pub const EXCHANGE_RATE_SCALE: u128 = 1_000_000_000_000_000_000_u128;
+
+/// Layout version of VaultState written by initialize and migrate_vault_state
+#[constant]
+pub const VAULT_STATE_VERSION: u8 = 1;
+
+// Scale of the u64 exchange rate stored by vaults created before rates were widened to u128
+const LEGACY_EXCHANGE_RATE_SCALE: u128 = 1_000_000;
+
+// Virtual assets and shares added to both sides of the share-based rate. A donation to a
+// vault with few shares is then mostly captured by the virtual share, so inflating the
+// share price to round a later deposit down costs the attacker more than it gains
+const VIRTUAL_ASSETS: u64 = 1;
+const VIRTUAL_SHARES: u64 = 1;
+
+// Basis point denominator: 10_000 bps = 100%
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// Default cap on the exchange rate increase per epoch (10%)
+const DEFAULT_MAX_RATE_INCREASE_BPS: u16 = 1_000;
+
+// Default minimum epoch duration (one day, in ~400ms slots and in seconds), so a new vault
+// cannot have its epochs advanced back to back
+const DEFAULT_MIN_EPOCH_SLOTS: u64 = 216_000;
+const DEFAULT_MIN_EPOCH_SECONDS: i64 = 86_400;
+
+// Length of the year the management fee is annualized over (365 days)
+const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+/// Pause flag: blocks deposit
+#[constant]
+pub const PAUSE_DEPOSIT: u8 = 1 << 0;
+/// Pause flag: blocks request_withdraw
+#[constant]
+pub const PAUSE_REQUEST_WITHDRAW: u8 = 1 << 1;
+/// Pause flag: blocks claim_withdraw
+#[constant]
+pub const PAUSE_CLAIM: u8 = 1 << 2;
+/// All pause flags combined
+#[constant]
+pub const PAUSE_ALL: u8 = PAUSE_DEPOSIT | PAUSE_REQUEST_WITHDRAW | PAUSE_CLAIM;
+
+/// Upper bound for the entry and exit fees, in basis points (10%)
+#[constant]
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Upper bound for the performance fee, in basis points (50%)
+#[constant]
+pub const MAX_PERFORMANCE_FEE_BPS: u16 = 5_000;
+
+/// Upper bound for the annual management fee, in basis points (5% per year)
+#[constant]
+pub const MAX_MANAGEMENT_FEE_BPS: u16 = 500;
 
 #[program]
 pub mod vault_program {
@@ -21,25 +84,98 @@ pub mod vault_program {
     /// - Admin must sign the transaction
     /// - VaultState must not already exist (enforced by init constraint)
     /// - Deposit mint and IOU mint must be valid token mints
+    /// - The IOU mint must already be controlled by the vault: vault_state is its mint
+    ///   authority, it has no freeze authority, no supply, and the deposit mint's decimals
+    /// - The vault's deposit token account is the vault_state's associated token account,
+    ///   created here if needed; every other instruction only accepts that account
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         let vault_state = &mut ctx.accounts.vault_state;
 
         // Set vault configuration
         vault_state.admin = ctx.accounts.admin.key();
+        vault_state.pending_admin = Pubkey::default();
         vault_state.deposit_mint = ctx.accounts.deposit_mint.key();
         vault_state.iou_mint = ctx.accounts.iou_mint.key();
+        vault_state.vault_token_account = ctx.accounts.vault_deposit_token_account.key();
+        vault_state.bump = ctx.bumps.vault_state;
+
+        // Current layout; the reserved bytes stay zeroed for future fields
+        vault_state.version = VAULT_STATE_VERSION;
+        vault_state.reserved = [0; 128];
 
         // Initialize exchange rate to 1:1 (EXCHANGE_RATE_SCALE)
         vault_state.exchange_rate = EXCHANGE_RATE_SCALE;
 
-        // Initialize epoch to 0
+        // Bound manual rate increases per epoch; losses stay disabled until a loss
+        // authority is configured
+        vault_state.max_rate_increase_bps = DEFAULT_MAX_RATE_INCREASE_BPS;
+        vault_state.epoch_start_rate = EXCHANGE_RATE_SCALE;
+        vault_state.loss_authority = Pubkey::default();
+
+        // Every operational role starts with the admin until delegated via set_role
+        vault_state.rate_manager = ctx.accounts.admin.key();
+        vault_state.yield_depositor = ctx.accounts.admin.key();
+        vault_state.pauser = ctx.accounts.admin.key();
+        vault_state.fee_manager = ctx.accounts.admin.key();
+
+        // All operations start unpaused
+        vault_state.paused_flags = 0;
+
+        // No fees until the fee manager configures them; the admin is the default treasury
+        vault_state.treasury = ctx.accounts.admin.key();
+        vault_state.entry_fee_bps = 0;
+        vault_state.exit_fee_bps = 0;
+
+        // Performance fees are only charged on gains above the starting rate
+        vault_state.performance_fee_bps = 0;
+        vault_state.high_water_mark = EXCHANGE_RATE_SCALE;
+
+        // Initialize epoch to 0; the admin advances epochs, at most once a day, until the
+        // epoch authority role is delegated or set_epoch_config changes the duration
+        let clock = Clock::get()?;
         vault_state.current_epoch = 0;
+        vault_state.epoch_authority = ctx.accounts.admin.key();
+        vault_state.min_epoch_slots = DEFAULT_MIN_EPOCH_SLOTS;
+        vault_state.min_epoch_seconds = DEFAULT_MIN_EPOCH_SECONDS;
+        vault_state.last_epoch_slot = clock.slot;
+        vault_state.last_epoch_timestamp = clock.unix_timestamp;
+
+        // No management fee until the fee manager sets one; accrual starts now
+        vault_state.management_fee_bps = 0;
+        vault_state.last_fee_accrual_ts = clock.unix_timestamp;
+
+        // Deposits are uncapped until the admin sets caps
+        vault_state.max_total_assets = 0;
+        vault_state.max_deposit_per_user = 0;
+
+        // No minimum amounts until the admin sets them
+        vault_state.min_deposit_amount = 0;
+        vault_state.min_redeem_iou_amount = 0;
+
+        // Start in manual rate mode with no pending withdrawals
+        vault_state.share_based_accounting = false;
+        vault_state.pending_iou_amount = 0;
+
+        // Tickets are priced at claim time until value locking is enabled
+        vault_state.lock_withdrawal_value = false;
+        vault_state.pending_withdrawal_assets = 0;
+
+        emit!(VaultInitializedEvent {
+            vault_state: vault_state.key(),
+            admin: vault_state.admin,
+            deposit_mint: vault_state.deposit_mint,
+            iou_mint: vault_state.iou_mint,
+            vault_token_account: vault_state.vault_token_account,
+            exchange_rate: vault_state.exchange_rate,
+            epoch: vault_state.current_epoch,
+        });
 
         msg!(
-            "Vault initialized: admin={}, deposit_mint={}, iou_mint={}, exchange_rate={}, epoch={}",
+            "Vault initialized: admin={}, deposit_mint={}, iou_mint={}, vault_token_account={}, exchange_rate={}, epoch={}",
             vault_state.admin,
             vault_state.deposit_mint,
             vault_state.iou_mint,
+            vault_state.vault_token_account,
             vault_state.exchange_rate,
             vault_state.current_epoch
         );
@@ -51,71 +187,94 @@ pub mod vault_program {
     ///
     /// Parameters:
     /// - deposit_amount: Amount of deposit tokens to transfer to the vault
+    /// - min_iou_out: Minimum IOU tokens the user accepts (0 = no slippage check)
     ///
     /// Security assumptions:
     /// - VaultState must be initialized
     /// - User must have sufficient deposit tokens
     /// - Exchange rate must be set (non-zero)
-    pub fn deposit(ctx: Context<Deposit>, deposit_amount: u64) -> Result<()> {
-        let vault_state = &ctx.accounts.vault_state;
+    /// - In share-based mode the rate is derived from vault holdings before the transfer
+    /// - The entry fee is sent to the treasury and IOUs are minted for the remainder only;
+    ///   the treasury's token accounts may be omitted while no entry or management fee is due
+    /// - The accrued management fee is collected before the deposit is priced
+    /// - The invested amount must fit under max_total_assets and, tracked in the user's
+    ///   position, under max_deposit_per_user (0 disables either cap)
+    /// - Deposits below min_deposit_amount, or too small to mint a single IOU unit, are
+    ///   rejected with DepositBelowMinimum
+    /// - The deposit reverts with SlippageExceeded if fewer than min_iou_out IOUs would be
+    ///   minted, e.g. because the rate moved before the transaction landed
+    /// - Deposits must not be paused
+    pub fn deposit(ctx: Context<Deposit>, deposit_amount: u64, min_iou_out: u64) -> Result<()> {
+        ctx.accounts.vault_state.require_not_paused(PAUSE_DEPOSIT)?;
 
-        // Ensure exchange rate is set
-        require!(
-            vault_state.exchange_rate > 0,
-            VaultError::InvalidExchangeRate
-        );
+        // Validate amount
+        require!(deposit_amount > 0, VaultError::InvalidAmount);
 
-        // Calculate IOU amount based on exchange rate
-        // Formula: iou_amount = (deposit_amount * EXCHANGE_RATE_SCALE) / exchange_rate
-        // When exchange_rate increases, users get fewer IOUs (IOU becomes more valuable)
-        // This ensures we maintain precision while avoiding overflow
-        let iou_amount = deposit_amount
-            .checked_mul(EXCHANGE_RATE_SCALE)
-            .ok_or(VaultError::MathOverflow)?
-            .checked_div(vault_state.exchange_rate)
+        let exchange_rate = price_deposit(ctx.accounts)?;
+
+        // Split off the entry fee; only the remainder is invested in the vault
+        let fee_amount = ctx.accounts.vault_state.entry_fee(deposit_amount)?;
+        let net_deposit_amount = deposit_amount
+            .checked_sub(fee_amount)
             .ok_or(VaultError::MathOverflow)?;
 
+        // Calculate IOU amount based on exchange rate, rounded down
+        // Formula: iou_amount = (net_deposit_amount * EXCHANGE_RATE_SCALE) / exchange_rate
+        // When exchange_rate increases, users get fewer IOUs (IOU becomes more valuable)
+        let iou_amount = assets_to_ious_floor(net_deposit_amount, exchange_rate)?;
+
+        // Dust that rounds to zero IOUs would be donated to the vault
+        require!(iou_amount > 0, VaultError::DepositBelowMinimum);
+        require!(iou_amount >= min_iou_out, VaultError::SlippageExceeded);
+
+        settle_deposit(
+            ctx.accounts,
+            deposit_amount,
+            fee_amount,
+            iou_amount,
+            exchange_rate,
+        )
+    }
+
+    /// Mint an exact amount of IOU tokens, paying whatever deposit tokens that costs at the
+    /// current exchange rate.
+    ///
+    /// Parameters:
+    /// - iou_amount: Amount of IOU tokens to mint to the user
+    /// - max_assets_in: Maximum deposit tokens the user pays, entry fee included
+    ///
+    /// Security assumptions:
+    /// - Same checks as deposit (pause flag, caps, minimum deposit, management fee)
+    /// - The required deposit is rounded up, both for the rate and for the entry fee, so
+    ///   the vault never mints IOUs worth more than it receives
+    /// - The instruction reverts with SlippageExceeded if the cost exceeds max_assets_in
+    pub fn mint_shares(ctx: Context<Deposit>, iou_amount: u64, max_assets_in: u64) -> Result<()> {
+        ctx.accounts.vault_state.require_not_paused(PAUSE_DEPOSIT)?;
+
+        // Validate amount
         require!(iou_amount > 0, VaultError::InvalidAmount);
 
-        // Transfer deposit tokens from user to vault
-        let deposit_mint_decimals = ctx.accounts.deposit_mint.decimals;
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                mint: ctx.accounts.deposit_mint.to_account_info(),
-                from: ctx.accounts.user_deposit_token_account.to_account_info(),
-                to: ctx.accounts.vault_deposit_token_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        );
-        token_interface::transfer_checked(transfer_ctx, deposit_amount, deposit_mint_decimals)?;
+        let exchange_rate = price_deposit(ctx.accounts)?;
 
-        // Mint IOU tokens to user
-        // The vault_state PDA must be the mint authority for the IOU mint
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            b"vault_state",
-            vault_state.deposit_mint.as_ref(),
-            &[ctx.bumps.vault_state],
-        ]];
-        let mint_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.iou_mint.to_account_info(),
-                to: ctx.accounts.user_iou_token_account.to_account_info(),
-                authority: ctx.accounts.vault_state.to_account_info(),
-            },
-            signer_seeds,
+        // Calculate the invested amount backing iou_amount, rounded up
+        // Formula: net = ceil(iou_amount * exchange_rate / EXCHANGE_RATE_SCALE)
+        let net_required = ious_to_assets_ceil(iou_amount, exchange_rate)?;
+
+        // Gross up for the entry fee so that what remains after the fee still covers it
+        let deposit_amount = ctx.accounts.vault_state.gross_up_entry_fee(net_required)?;
+        let fee_amount = ctx.accounts.vault_state.entry_fee(deposit_amount)?;
+        require!(
+            deposit_amount <= max_assets_in,
+            VaultError::SlippageExceeded
         );
-        token_interface::mint_to(mint_ctx, iou_amount)?;
 
-        msg!(
-            "Deposited {} deposit tokens, received {} IOU tokens (exchange_rate: {})",
+        settle_deposit(
+            ctx.accounts,
             deposit_amount,
+            fee_amount,
             iou_amount,
-            vault_state.exchange_rate
-        );
-
-        Ok(())
+            exchange_rate,
+        )
     }
 
     /// Request withdrawal by burning IOU tokens and creating a withdrawal ticket.
@@ -125,75 +284,114 @@ pub mod vault_program {
     ///
     /// Security assumptions:
     /// - User must have sufficient IOU tokens
-    /// - User must not have an existing unclaimed withdrawal ticket
+    /// - Each request opens a new ticket at the user's next ticket index, so several
+    ///   tickets can be pending at once
     /// - VaultState must be initialized
+    /// - When lock_withdrawal_value is set, the owed deposit amount is fixed at the
+    ///   request-time rate and reserved in pending_withdrawal_assets
+    /// - The accrued management fee is collected before the ticket is priced
+    /// - Requests below min_redeem_iou_amount are rejected with RedeemBelowMinimum unless
+    ///   they redeem the user's whole IOU balance, so small tickets cannot be spammed but
+    ///   leftover balances can always exit
+    /// - Withdrawal requests must not be paused
     pub fn request_withdraw(ctx: Context<RequestWithdraw>, iou_amount: u64) -> Result<()> {
-        let vault_state = &mut ctx.accounts.vault_state;
+        ctx.accounts
+            .vault_state
+            .require_not_paused(PAUSE_REQUEST_WITHDRAW)?;
 
         // Validate amount
         require!(iou_amount > 0, VaultError::InvalidAmount);
 
-        // Check if withdrawal ticket already exists and is not claimed
-        // If account was just created (init_if_needed), user will be Pubkey::default()
-        // If account exists, check if it's already claimed or belongs to different user
-        let withdrawal_ticket = &mut ctx.accounts.withdrawal_ticket;
-        let is_new_account = withdrawal_ticket.user == Pubkey::default();
-
-        if !is_new_account {
-            // Account already exists - ensure it belongs to this user
-            require!(
-                withdrawal_ticket.user == ctx.accounts.user.key(),
-                VaultError::InvalidTicketOwner
-            );
-            // Ensure previous ticket was claimed before creating a new one
-            require!(withdrawal_ticket.claimed, VaultError::TicketAlreadyClaimed);
-        }
-
-        // Burn IOU tokens from user's account
-        let iou_mint_decimals = ctx.accounts.iou_mint.decimals;
-        let burn_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            BurnChecked {
-                mint: ctx.accounts.iou_mint.to_account_info(),
-                from: ctx.accounts.user_iou_token_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        );
-        token_interface::burn_checked(burn_ctx, iou_amount, iou_mint_decimals)?;
-
-        // Create withdrawal ticket with unlock_epoch = current_epoch + 1
-        let unlock_epoch = vault_state
-            .current_epoch
-            .checked_add(1)
-            .ok_or(VaultError::MathOverflow)?;
+        collect_management_fee(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.iou_mint,
+            ctx.accounts.treasury_iou_token_account.as_ref(),
+            &mut ctx.accounts.vault_state,
+        )?;
 
-        withdrawal_ticket.user = ctx.accounts.user.key();
-        withdrawal_ticket.iou_amount = iou_amount;
-        withdrawal_ticket.unlock_epoch = unlock_epoch;
-        withdrawal_ticket.claimed = false;
+        open_withdrawal_ticket(ctx.accounts, ctx.bumps.withdrawal_ticket, iou_amount)
+    }
 
-        msg!(
-            "Requested withdrawal: {} IOU tokens burned, unlock_epoch: {}",
-            iou_amount,
-            unlock_epoch
-        );
+    /// Request a withdrawal of a deposit token amount, burning the IOU tokens it is worth
+    /// at the current exchange rate and creating a normal withdrawal ticket.
+    ///
+    /// Parameters:
+    /// - asset_amount: Deposit tokens to redeem, before the exit fee taken at claim time
+    /// - max_iou_burn: Maximum IOU tokens the user accepts to burn
+    ///
+    /// Security assumptions:
+    /// - Same checks as request_withdraw (pause flag, minimum, management fee)
+    /// - The IOU amount is rounded up, so the ticket is never worth less than asset_amount
+    ///   at the request-time rate and the user cannot redeem value they did not burn
+    /// - The ticket is priced like any other: unlocked tickets are paid at the claim-time
+    ///   rate, locked tickets the snapshotted amount
+    /// - The request reverts with SlippageExceeded if more than max_iou_burn IOUs are needed
+    pub fn request_withdraw_assets(
+        ctx: Context<RequestWithdraw>,
+        asset_amount: u64,
+        max_iou_burn: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .vault_state
+            .require_not_paused(PAUSE_REQUEST_WITHDRAW)?;
 
-        Ok(())
+        // Validate amount
+        require!(asset_amount > 0, VaultError::InvalidAmount);
+
+        collect_management_fee(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.iou_mint,
+            ctx.accounts.treasury_iou_token_account.as_ref(),
+            &mut ctx.accounts.vault_state,
+        )?;
+
+        let exchange_rate = ctx.accounts.vault_state.current_exchange_rate(
+            ctx.accounts.vault_deposit_token_account.amount,
+            ctx.accounts.iou_mint.supply,
+            Rounding::Down,
+        )?;
+        require!(exchange_rate > 0, VaultError::InvalidExchangeRate);
+
+        // Calculate the IOU amount worth asset_amount, rounded up
+        // Formula: iou_amount = ceil(asset_amount * EXCHANGE_RATE_SCALE / exchange_rate)
+        let iou_amount = assets_to_ious_ceil(asset_amount, exchange_rate)?;
+        require!(iou_amount <= max_iou_burn, VaultError::SlippageExceeded);
+
+        open_withdrawal_ticket(ctx.accounts, ctx.bumps.withdrawal_ticket, iou_amount)
     }
 
     /// Claim withdrawal by transferring deposit tokens from vault to user.
     ///
     /// Parameters:
-    /// - None (uses withdrawal ticket data)
+    /// - min_assets_out: Minimum deposit tokens the user accepts after the exit fee
+    ///   (0 = no slippage check)
     ///
     /// Security assumptions:
     /// - Withdrawal ticket must exist and belong to the user
-    /// - Ticket must not be already claimed
     /// - Current epoch must be >= unlock_epoch
     /// - Vault must have sufficient deposit tokens
-    pub fn claim_withdraw(ctx: Context<ClaimWithdraw>) -> Result<()> {
+    /// - Tickets with a locked value are paid exactly the snapshotted amount; other tickets
+    ///   cannot dip into assets reserved for locked tickets
+    /// - The exit fee is taken from the redeemed amount and sent to the treasury; without
+    ///   the treasury's token accounts the claim only succeeds while no exit or management
+    ///   fee is due, so a treasury that closed them cannot block exits from a fee-free vault
+    /// - The accrued management fee is collected before the claim is priced
+    /// - The ticket is closed on success (rent refunded to the user), so it cannot be claimed twice
+    /// - The claim reverts with SlippageExceeded if the user would receive fewer than
+    ///   min_assets_out deposit tokens
+    /// - Claims must not be paused
+    pub fn claim_withdraw(ctx: Context<ClaimWithdraw>, min_assets_out: u64) -> Result<()> {
+        ctx.accounts.vault_state.require_not_paused(PAUSE_CLAIM)?;
+
+        collect_management_fee(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.iou_mint,
+            ctx.accounts.treasury_iou_token_account.as_ref(),
+            &mut ctx.accounts.vault_state,
+        )?;
+
         let vault_state = &ctx.accounts.vault_state;
-        let withdrawal_ticket = &mut ctx.accounts.withdrawal_ticket;
+        let withdrawal_ticket = &ctx.accounts.withdrawal_ticket;
 
         // Validate ticket ownership
         require!(
@@ -201,43 +399,65 @@ pub mod vault_program {
             VaultError::InvalidTicketOwner
         );
 
-        // Ensure ticket is not already claimed
-        require!(!withdrawal_ticket.claimed, VaultError::TicketAlreadyClaimed);
-
         // Ensure unlock epoch has been reached
         require!(
             vault_state.current_epoch >= withdrawal_ticket.unlock_epoch,
             VaultError::WithdrawalNotReady
         );
 
-        // Calculate deposit token amount based on current exchange rate
+        // Calculate deposit token amount based on current exchange rate, rounded down
         // Formula: deposit_amount = (iou_amount * exchange_rate) / EXCHANGE_RATE_SCALE
         // When exchange_rate increases, users get more tokens back (IOU becomes more valuable)
         // This ensures users benefit from yield when the exchange rate increases
-        let deposit_amount = withdrawal_ticket
-            .iou_amount
-            .checked_mul(vault_state.exchange_rate)
-            .ok_or(VaultError::MathOverflow)?
-            .checked_div(EXCHANGE_RATE_SCALE)
-            .ok_or(VaultError::MathOverflow)?;
+        let exchange_rate = vault_state.current_exchange_rate(
+            ctx.accounts.vault_deposit_token_account.amount,
+            ctx.accounts.iou_mint.supply,
+            Rounding::Down,
+        )?;
+        let value_locked = withdrawal_ticket.locked_deposit_amount > 0;
+        let deposit_amount = if value_locked {
+            withdrawal_ticket.locked_deposit_amount
+        } else {
+            ious_to_assets_floor(withdrawal_ticket.iou_amount, exchange_rate)?
+        };
 
         require!(deposit_amount > 0, VaultError::InvalidAmount);
 
         // Ensure vault has sufficient tokens to fulfill the withdrawal
         // This prevents undercollateralization issues when exchange rate increases
-        // without corresponding token deposits
+        // without corresponding token deposits. Assets reserved for locked tickets are
+        // only available to those tickets.
+        let available_assets = if value_locked {
+            ctx.accounts.vault_deposit_token_account.amount
+        } else {
+            ctx.accounts
+                .vault_deposit_token_account
+                .amount
+                .saturating_sub(vault_state.pending_withdrawal_assets)
+        };
         require!(
-            ctx.accounts.vault_deposit_token_account.amount >= deposit_amount,
+            available_assets >= deposit_amount,
             VaultError::InsufficientVaultBalance
         );
 
+        // Split off the exit fee; the user receives the remainder
+        let fee_amount = vault_state.exit_fee(deposit_amount)?;
+        let net_deposit_amount = deposit_amount
+            .checked_sub(fee_amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(net_deposit_amount > 0, VaultError::InvalidAmount);
+        require!(
+            net_deposit_amount >= min_assets_out,
+            VaultError::SlippageExceeded
+        );
+
         // Transfer deposit tokens from vault to user
         // The vault_state PDA is the authority for the vault's deposit token account
         let deposit_mint_decimals = ctx.accounts.deposit_mint.decimals;
         let signer_seeds: &[&[&[u8]]] = &[&[
             b"vault_state",
             vault_state.deposit_mint.as_ref(),
-            &[ctx.bumps.vault_state],
+            &[vault_state.bump],
         ]];
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
@@ -249,54 +469,250 @@ pub mod vault_program {
             },
             signer_seeds,
         );
-        token_interface::transfer_checked(transfer_ctx, deposit_amount, deposit_mint_decimals)?;
+        token_interface::transfer_checked(transfer_ctx, net_deposit_amount, deposit_mint_decimals)?;
+
+        // Transfer the exit fee from vault to treasury
+        if fee_amount > 0 {
+            let treasury_deposit_token_account = ctx
+                .accounts
+                .treasury_deposit_token_account
+                .as_ref()
+                .ok_or(VaultError::TreasuryAccountMissing)?;
+            let fee_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    mint: ctx.accounts.deposit_mint.to_account_info(),
+                    from: ctx.accounts.vault_deposit_token_account.to_account_info(),
+                    to: treasury_deposit_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_state.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token_interface::transfer_checked(fee_ctx, fee_amount, deposit_mint_decimals)?;
+        }
 
-        // Mark ticket as claimed
-        withdrawal_ticket.claimed = true;
+        // Release the ticket's reservation (locked) or its outstanding IOUs (unlocked)
+        let iou_amount = withdrawal_ticket.iou_amount;
+        let ticket_index = withdrawal_ticket.ticket_index;
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.exchange_rate = exchange_rate;
+        if value_locked {
+            vault_state.pending_withdrawal_assets = vault_state
+                .pending_withdrawal_assets
+                .checked_sub(deposit_amount)
+                .ok_or(VaultError::MathOverflow)?;
+        } else {
+            vault_state.pending_iou_amount = vault_state
+                .pending_iou_amount
+                .checked_sub(iou_amount)
+                .ok_or(VaultError::MathOverflow)?;
+        }
 
-        msg!(
-            "Claimed withdrawal: {} deposit tokens transferred (iou_amount: {}, exchange_rate: {})",
+        // The ticket itself is closed by the `close = user` constraint once this returns
+        let user_position = &mut ctx.accounts.user_position;
+        user_position.open_tickets = user_position
+            .open_tickets
+            .checked_sub(1)
+            .ok_or(VaultError::MathOverflow)?;
+
+        // Free up deposit cap room (withdrawals that include yield can exceed what was deposited)
+        user_position.total_deposited =
+            user_position.total_deposited.saturating_sub(deposit_amount);
+
+        emit!(WithdrawClaimedEvent {
+            vault_state: vault_state.key(),
+            user: ctx.accounts.user.key(),
+            ticket_index,
+            iou_amount,
             deposit_amount,
-            withdrawal_ticket.iou_amount,
-            vault_state.exchange_rate
+            fee_amount,
+            exchange_rate,
+            epoch: vault_state.current_epoch,
+        });
+
+        msg!(
+            "Claimed withdrawal: {} deposit tokens transferred (fee: {}, ticket_index: {}, iou_amount: {}, exchange_rate: {})",
+            net_deposit_amount,
+            fee_amount,
+            ticket_index,
+            iou_amount,
+            exchange_rate
         );
 
         Ok(())
     }
 
-    /// Increase the exchange rate to simulate yield growth (admin-only).
+    /// Increase the exchange rate to simulate yield growth (rate-manager-only).
+    /// The epoch is not affected; use advance_epoch for that.
     ///
     /// Parameters:
     /// - new_exchange_rate: New exchange rate value (scaled by EXCHANGE_RATE_SCALE)
     ///
     /// Security assumptions:
-    /// - Only the admin can call this instruction
-    /// - New exchange rate must be greater than zero
-    /// - Exchange rate should typically increase to simulate yield
-    pub fn increase_rate(ctx: Context<IncreaseRate>, new_exchange_rate: u64) -> Result<()> {
+    /// - Only the rate manager can call this instruction
+    /// - New exchange rate must not be lower than the current rate (use apply_loss)
+    /// - New exchange rate must stay within max_rate_increase_bps of the rate the
+    ///   current epoch started at
+    /// - Disabled in share-based mode, where the rate tracks vault holdings
+    /// - Any gain above the high-water mark pays the performance fee (see
+    ///   VaultState::accrue_performance_fee)
+    pub fn increase_rate(ctx: Context<IncreaseRate>, new_exchange_rate: u128) -> Result<()> {
         let vault_state = &mut ctx.accounts.vault_state;
 
-        // Validate admin authority
+        // Validate rate manager authority
         require!(
-            ctx.accounts.admin.key() == vault_state.admin,
-            VaultError::UnauthorizedAdmin
+            ctx.accounts.rate_manager.key() == vault_state.rate_manager,
+            VaultError::UnauthorizedRole
         );
 
         // Validate new exchange rate
         require!(new_exchange_rate > 0, VaultError::InvalidExchangeRate);
+        require!(
+            !vault_state.share_based_accounting,
+            VaultError::ManualRateDisabled
+        );
+
+        // The rate only moves up here; decreases go through apply_loss
+        require!(
+            new_exchange_rate >= vault_state.exchange_rate,
+            VaultError::RateDecreaseNotAllowed
+        );
+
+        // Cap the cumulative increase within the current epoch
+        let max_exchange_rate = vault_state.max_exchange_rate()?;
+        require!(
+            new_exchange_rate <= max_exchange_rate,
+            VaultError::RateIncreaseTooLarge
+        );
 
         // Update exchange rate
         let old_exchange_rate = vault_state.exchange_rate;
         vault_state.exchange_rate = new_exchange_rate;
 
-        // Increment current epoch
+        // Charge the performance fee on the part of the gain above the high-water mark
+        let fee_shares = collect_performance_fee(
+            &ctx.accounts.token_program,
+            &ctx.accounts.iou_mint,
+            Some(&ctx.accounts.treasury_iou_token_account),
+            &mut ctx.accounts.vault_state,
+        )?;
+
+        let vault_state = &ctx.accounts.vault_state;
+        let new_exchange_rate = vault_state.exchange_rate;
+
+        emit!(RateUpdatedEvent {
+            vault_state: vault_state.key(),
+            old_exchange_rate,
+            new_exchange_rate,
+            epoch: vault_state.current_epoch,
+        });
+
+        msg!(
+            "Exchange rate increased from {} to {} (epoch: {}, performance fee: {} IOU)",
+            old_exchange_rate,
+            new_exchange_rate,
+            vault_state.current_epoch,
+            fee_shares
+        );
+
+        Ok(())
+    }
+
+    /// Advance the vault to the next epoch (epoch-authority-only).
+    /// Withdrawal tickets unlock once current_epoch reaches their unlock_epoch.
+    ///
+    /// Parameters:
+    /// - None (the epoch is always incremented by one)
+    ///
+    /// Security assumptions:
+    /// - Only the epoch authority can call this instruction
+    /// - At least min_epoch_slots slots and min_epoch_seconds seconds must have passed since
+    ///   the previous epoch started, so epochs cannot be spammed forward to skip the
+    ///   withdrawal delay
+    pub fn advance_epoch(ctx: Context<AdvanceEpoch>) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        let clock = Clock::get()?;
+
+        // Enforce the minimum epoch duration in both slots and wall-clock time
+        let next_epoch_slot = vault_state
+            .last_epoch_slot
+            .checked_add(vault_state.min_epoch_slots)
+            .ok_or(VaultError::MathOverflow)?;
+        let next_epoch_timestamp = vault_state
+            .last_epoch_timestamp
+            .checked_add(vault_state.min_epoch_seconds)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(
+            clock.slot >= next_epoch_slot && clock.unix_timestamp >= next_epoch_timestamp,
+            VaultError::EpochTooEarly
+        );
+
+        // Increment current epoch and record when it started
         vault_state.current_epoch = vault_state
             .current_epoch
             .checked_add(1)
             .ok_or(VaultError::MathOverflow)?;
+        vault_state.last_epoch_slot = clock.slot;
+        vault_state.last_epoch_timestamp = clock.unix_timestamp;
+
+        // The per-epoch rate increase bound restarts from the current rate
+        vault_state.epoch_start_rate = vault_state.exchange_rate;
+
+        emit!(EpochAdvancedEvent {
+            vault_state: vault_state.key(),
+            epoch: vault_state.current_epoch,
+            exchange_rate: vault_state.exchange_rate,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Epoch advanced to {} (slot: {}, unix_timestamp: {})",
+            vault_state.current_epoch,
+            clock.slot,
+            clock.unix_timestamp
+        );
+
+        Ok(())
+    }
+
+    /// Lower the exchange rate to socialize a loss or slashing event (rate manager + loss authority).
+    ///
+    /// Parameters:
+    /// - new_exchange_rate: New, lower exchange rate value (scaled by EXCHANGE_RATE_SCALE)
+    ///
+    /// Security assumptions:
+    /// - Both the rate manager and the loss authority must sign
+    /// - New exchange rate must be greater than zero and lower than the current rate
+    /// - Disabled in share-based mode, where losses show up in the vault balance
+    /// - Emits LossAppliedEvent so rate decreases can be monitored separately
+    pub fn apply_loss(ctx: Context<ApplyLoss>, new_exchange_rate: u128) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        require!(
+            !vault_state.share_based_accounting,
+            VaultError::ManualRateDisabled
+        );
+        require!(
+            new_exchange_rate > 0 && new_exchange_rate < vault_state.exchange_rate,
+            VaultError::InvalidExchangeRate
+        );
+
+        let old_exchange_rate = vault_state.exchange_rate;
+        vault_state.exchange_rate = new_exchange_rate;
+
+        // Later increases in this epoch are bounded from the reduced rate
+        vault_state.epoch_start_rate = new_exchange_rate;
+
+        emit!(LossAppliedEvent {
+            vault_state: vault_state.key(),
+            old_exchange_rate,
+            new_exchange_rate,
+            epoch: vault_state.current_epoch,
+        });
 
         msg!(
-            "Exchange rate increased from {} to {}, epoch incremented to {}",
+            "Loss applied: exchange rate decreased from {} to {} (epoch: {})",
             old_exchange_rate,
             new_exchange_rate,
             vault_state.current_epoch
@@ -305,314 +721,2908 @@ pub mod vault_program {
         Ok(())
     }
 
-    /// Deposit yield tokens into the vault (admin-only).
+    /// Configure the per-epoch rate increase bound and the loss authority (admin-only).
+    ///
+    /// Parameters:
+    /// - max_rate_increase_bps: Maximum increase of the exchange rate per epoch, in basis points
+    /// - loss_authority: Key that must co-sign apply_loss (Pubkey::default() disables losses)
+    ///
+    /// Security assumptions:
+    /// - Only the admin can call this instruction
+    /// - max_rate_increase_bps must not exceed BPS_DENOMINATOR (a 100% increase)
+    pub fn set_rate_config(
+        ctx: Context<SetRateConfig>,
+        max_rate_increase_bps: u16,
+        loss_authority: Pubkey,
+    ) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        require!(
+            u64::from(max_rate_increase_bps) <= BPS_DENOMINATOR,
+            VaultError::InvalidRateConfig
+        );
+
+        vault_state.max_rate_increase_bps = max_rate_increase_bps;
+        vault_state.loss_authority = loss_authority;
+
+        emit!(RateConfigUpdatedEvent {
+            vault_state: vault_state.key(),
+            max_rate_increase_bps,
+            loss_authority,
+        });
+
+        msg!(
+            "Rate config updated: max_rate_increase_bps={}, loss_authority={}",
+            max_rate_increase_bps,
+            loss_authority
+        );
+
+        Ok(())
+    }
+
+    /// Configure the minimum epoch duration (admin-only). The key allowed to advance epochs
+    /// is the EpochAuthority role, assigned with set_role.
+    ///
+    /// Parameters:
+    /// - min_epoch_slots: Minimum number of slots between epoch advances
+    /// - min_epoch_seconds: Minimum number of seconds between epoch advances
+    ///
+    /// Security assumptions:
+    /// - Only the admin can call this instruction
+    /// - min_epoch_seconds must not be negative
+    /// - Vaults start with a one-day minimum in both slots and seconds; setting both to 0
+    ///   lets the epoch authority advance epochs back to back
+    pub fn set_epoch_config(
+        ctx: Context<SetEpochConfig>,
+        min_epoch_slots: u64,
+        min_epoch_seconds: i64,
+    ) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        require!(min_epoch_seconds >= 0, VaultError::InvalidEpochConfig);
+
+        vault_state.min_epoch_slots = min_epoch_slots;
+        vault_state.min_epoch_seconds = min_epoch_seconds;
+
+        emit!(EpochConfigUpdatedEvent {
+            vault_state: vault_state.key(),
+            min_epoch_slots,
+            min_epoch_seconds,
+        });
+
+        msg!(
+            "Epoch config updated: min_epoch_slots={}, min_epoch_seconds={}",
+            min_epoch_slots,
+            min_epoch_seconds
+        );
+
+        Ok(())
+    }
+
+    /// Deposit yield tokens into the vault (yield-depositor-only).
     /// This represents staking rewards, yield, or other income that benefits existing holders.
     /// No IOU tokens are minted - the yield increases the value of existing IOUs.
+    /// In share-based mode the exchange rate rises automatically with the added assets.
     ///
     /// Parameters:
     /// - yield_amount: Amount of deposit tokens to transfer to the vault
     ///
     /// Security assumptions:
-    /// - Only the admin can call this instruction
-    /// - Admin must have sufficient deposit tokens
+    /// - Only the yield depositor can call this instruction
+    /// - Yield depositor must have sufficient deposit tokens
     /// - VaultState must be initialized
+    /// - Any gain above the high-water mark pays the performance fee (see
+    ///   VaultState::accrue_performance_fee)
     pub fn deposit_yield(ctx: Context<DepositYield>, yield_amount: u64) -> Result<()> {
         let vault_state = &ctx.accounts.vault_state;
 
-        // Validate admin authority
+        // Validate yield depositor authority
         require!(
-            ctx.accounts.admin.key() == vault_state.admin,
-            VaultError::UnauthorizedAdmin
+            ctx.accounts.yield_depositor.key() == vault_state.yield_depositor,
+            VaultError::UnauthorizedRole
         );
 
         // Validate amount
         require!(yield_amount > 0, VaultError::InvalidAmount);
 
-        // Transfer deposit tokens from admin to vault
+        // Transfer deposit tokens from the yield depositor to vault
         // This represents yield/staking rewards that benefit existing IOU holders
         let deposit_mint_decimals = ctx.accounts.deposit_mint.decimals;
         let transfer_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             TransferChecked {
                 mint: ctx.accounts.deposit_mint.to_account_info(),
-                from: ctx.accounts.admin_deposit_token_account.to_account_info(),
+                from: ctx.accounts.yield_depositor_token_account.to_account_info(),
                 to: ctx.accounts.vault_deposit_token_account.to_account_info(),
-                authority: ctx.accounts.admin.to_account_info(),
+                authority: ctx.accounts.yield_depositor.to_account_info(),
             },
         );
         token_interface::transfer_checked(transfer_ctx, yield_amount, deposit_mint_decimals)?;
 
+        // Re-read the vault balance so a derived rate reflects the new assets
+        ctx.accounts.vault_deposit_token_account.reload()?;
+        let exchange_rate = ctx.accounts.vault_state.current_exchange_rate(
+            ctx.accounts.vault_deposit_token_account.amount,
+            ctx.accounts.iou_mint.supply,
+            Rounding::Down,
+        )?;
+        ctx.accounts.vault_state.exchange_rate = exchange_rate;
+
+        // Charge the performance fee on the part of the gain above the high-water mark
+        let fee_shares = collect_performance_fee(
+            &ctx.accounts.token_program,
+            &ctx.accounts.iou_mint,
+            Some(&ctx.accounts.treasury_iou_token_account),
+            &mut ctx.accounts.vault_state,
+        )?;
+        let exchange_rate = ctx.accounts.vault_state.exchange_rate;
+
+        emit!(YieldDepositedEvent {
+            vault_state: ctx.accounts.vault_state.key(),
+            yield_depositor: ctx.accounts.yield_depositor.key(),
+            yield_amount,
+            exchange_rate,
+            epoch: ctx.accounts.vault_state.current_epoch,
+        });
+
         msg!(
-            "Deposited {} yield tokens into vault (no IOU tokens minted - yield benefits existing holders)",
-            yield_amount
+            "Deposited {} yield tokens into vault (no IOU tokens minted to the depositor - yield benefits existing holders, exchange_rate: {}, performance fee: {} IOU)",
+            yield_amount,
+            exchange_rate,
+            fee_shares
         );
 
         Ok(())
     }
-}
 
-/// VaultState stores the global vault configuration and state.
-/// This is a PDA derived from the deposit_mint to ensure one vault per deposit token type.
-#[account]
-pub struct VaultState {
-    /// Admin authority that can update exchange rate
-    pub admin: Pubkey,
-    /// The mint of tokens that can be deposited into the vault
-    pub deposit_mint: Pubkey,
-    /// The mint of IOU tokens representing shares in the vault
-    pub iou_mint: Pubkey,
-    /// Exchange rate: iou_amount = deposit_amount * EXCHANGE_RATE_SCALE / exchange_rate
-    /// When exchange_rate increases, IOU becomes more valuable (yield-bearing behavior)
-    /// Scaled by EXCHANGE_RATE_SCALE (1_000_000) for precision
-    /// Example: exchange_rate = 1_100_000 means 1 IOU = 1.1 tokens
-    pub exchange_rate: u64,
-    /// Current epoch number (incremented by admin via increase_rate)
-    pub current_epoch: u64,
-}
+    /// Switch between manual and share-based (ERC-4626 style) accounting (admin-only).
+    ///
+    /// Parameters:
+    /// - enabled: true to derive the exchange rate from vault assets / outstanding IOUs,
+    ///   false to go back to the admin-set rate
+    ///
+    /// Security assumptions:
+    /// - Only the admin can call this instruction
+    /// - The stored rate is re-anchored to the asset-backed rate on the switch, so pricing
+    ///   stays continuous when leaving share-based mode
+    pub fn set_share_based_accounting(
+        ctx: Context<SetShareBasedAccounting>,
+        enabled: bool,
+    ) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
 
-/// WithdrawalTicket represents a pending withdrawal request.
-/// Users must wait until unlock_epoch before claiming their withdrawal.
-#[account]
-pub struct WithdrawalTicket {
-    /// The user who requested the withdrawal
-    pub user: Pubkey,
-    /// Amount of IOU tokens that were burned for this withdrawal
-    pub iou_amount: u64,
-    /// Epoch when the withdrawal can be claimed (current_epoch + 1 when created)
-    pub unlock_epoch: u64,
-    /// Whether this withdrawal has been claimed
-    pub claimed: bool,
-}
+        // Enabling starts from the asset-backed rate; disabling freezes the last derived
+        // rate as the new manual rate
+        if enabled || vault_state.share_based_accounting {
+            vault_state.exchange_rate = vault_state.share_exchange_rate(
+                ctx.accounts.vault_deposit_token_account.amount,
+                ctx.accounts.iou_mint.supply,
+                Rounding::Down,
+            )?;
+            vault_state.epoch_start_rate = vault_state.exchange_rate;
+        }
+        vault_state.share_based_accounting = enabled;
 
-/// Context for the initialize instruction.
-/// Creates the VaultState PDA account and sets initial configuration.
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    /// The admin authority that will control the vault (must sign and pay for account creation)
-    #[account(mut)]
-    pub admin: Signer<'info>,
+        emit!(ShareBasedAccountingUpdatedEvent {
+            vault_state: vault_state.key(),
+            enabled,
+            exchange_rate: vault_state.exchange_rate,
+        });
 
-    /// The vault state PDA
-    /// Seeds: ["vault_state", deposit_mint]
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + 32 + 32 + 32 + 8 + 8, // discriminator + admin + deposit_mint + iou_mint + exchange_rate + current_epoch
-        seeds = [b"vault_state", deposit_mint.key().as_ref()],
-        bump
-    )]
-    pub vault_state: Account<'info, VaultState>,
+        msg!(
+            "Share-based accounting {} (exchange_rate: {})",
+            if enabled { "enabled" } else { "disabled" },
+            vault_state.exchange_rate
+        );
 
-    /// The deposit token mint (used in PDA seeds)
-    pub deposit_mint: InterfaceAccount<'info, Mint>,
+        Ok(())
+    }
 
-    /// The IOU token mint (stored in VaultState)
-    pub iou_mint: InterfaceAccount<'info, Mint>,
+    /// Choose whether new withdrawal tickets lock their redemption value at request time (admin-only).
+    ///
+    /// Parameters:
+    /// - enabled: true to snapshot the owed deposit amount into new tickets,
+    ///   false to price new tickets at the claim-time exchange rate
+    ///
+    /// Security assumptions:
+    /// - Only the admin can call this instruction
+    /// - Existing tickets keep the pricing they were created with
+    pub fn set_lock_withdrawal_value(
+        ctx: Context<SetLockWithdrawalValue>,
+        enabled: bool,
+    ) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.lock_withdrawal_value = enabled;
 
-    /// System program for account creation
-    pub system_program: Program<'info, System>,
+        emit!(LockWithdrawalValueUpdatedEvent {
+            vault_state: vault_state.key(),
+            enabled,
+        });
+
+        msg!(
+            "Withdrawal value locking {} (pending_withdrawal_assets: {})",
+            if enabled { "enabled" } else { "disabled" },
+            vault_state.pending_withdrawal_assets
+        );
+
+        Ok(())
+    }
+
+    /// Set the deposit caps (admin-only).
+    ///
+    /// Parameters:
+    /// - max_total_assets: Maximum deposit tokens managed by the vault (0 = unlimited)
+    /// - max_deposit_per_user: Maximum outstanding deposits per user position (0 = unlimited)
+    ///
+    /// Security assumptions:
+    /// - Only the admin can call this instruction
+    /// - Lowering a cap below current holdings only blocks new deposits; withdrawals are
+    ///   never affected
+    pub fn set_deposit_caps(
+        ctx: Context<SetDepositCaps>,
+        max_total_assets: u64,
+        max_deposit_per_user: u64,
+    ) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.max_total_assets = max_total_assets;
+        vault_state.max_deposit_per_user = max_deposit_per_user;
+
+        emit!(DepositCapsUpdatedEvent {
+            vault_state: vault_state.key(),
+            max_total_assets,
+            max_deposit_per_user,
+        });
+
+        msg!(
+            "Deposit caps updated: max_total_assets={}, max_deposit_per_user={}",
+            max_total_assets,
+            max_deposit_per_user
+        );
+
+        Ok(())
+    }
+
+    /// Set the minimum deposit and redemption amounts (admin-only).
+    ///
+    /// Parameters:
+    /// - min_deposit_amount: Smallest deposit accepted, in deposit tokens (0 = no minimum)
+    /// - min_redeem_iou_amount: Smallest withdrawal request accepted, in IOU tokens
+    ///   (0 = no minimum)
+    ///
+    /// Security assumptions:
+    /// - Only the admin can call this instruction
+    /// - Users can always redeem their whole IOU balance, so a high minimum cannot trap funds
+    pub fn set_minimums(
+        ctx: Context<SetMinimums>,
+        min_deposit_amount: u64,
+        min_redeem_iou_amount: u64,
+    ) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.min_deposit_amount = min_deposit_amount;
+        vault_state.min_redeem_iou_amount = min_redeem_iou_amount;
+
+        emit!(MinimumsUpdatedEvent {
+            vault_state: vault_state.key(),
+            min_deposit_amount,
+            min_redeem_iou_amount,
+        });
+
+        msg!(
+            "Minimums updated: min_deposit_amount={}, min_redeem_iou_amount={}",
+            min_deposit_amount,
+            min_redeem_iou_amount
+        );
+
+        Ok(())
+    }
+
+    /// Propose a new admin (admin-only). Takes effect once the new admin calls accept_admin.
+    ///
+    /// Parameters:
+    /// - new_admin: Key proposed as the next admin (Pubkey::default() cancels a pending proposal)
+    ///
+    /// Security assumptions:
+    /// - Only the current admin can call this instruction
+    /// - The current admin stays in control until the proposal is accepted, so a mistyped
+    ///   key cannot lock the vault
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.pending_admin = new_admin;
+
+        emit!(AdminProposedEvent {
+            vault_state: vault_state.key(),
+            admin: vault_state.admin,
+            pending_admin: new_admin,
+        });
+
+        msg!(
+            "Admin transfer proposed: admin={}, pending_admin={}",
+            vault_state.admin,
+            new_admin
+        );
+
+        Ok(())
+    }
+
+    /// Accept a pending admin transfer (pending-admin-only).
+    ///
+    /// Parameters:
+    /// - None (the signer must be vault_state.pending_admin)
+    ///
+    /// Security assumptions:
+    /// - Only the proposed admin can accept, proving control of the new key
+    /// - The pending proposal is cleared once accepted
+    /// - Every role, the treasury and the loss authority still held by the old admin move
+    ///   to the new admin, so rotating a compromised admin key leaves it no power; roles
+    ///   delegated to other keys are kept
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        let old_admin = vault_state.admin;
+        let moved_roles = vault_state.transfer_admin(ctx.accounts.pending_admin.key());
+
+        for role in moved_roles {
+            emit!(RoleUpdatedEvent {
+                vault_state: vault_state.key(),
+                role,
+                authority: vault_state.admin,
+            });
+        }
+
+        emit!(AdminAcceptedEvent {
+            vault_state: vault_state.key(),
+            old_admin,
+            new_admin: vault_state.admin,
+        });
+
+        msg!(
+            "Admin transferred from {} to {}",
+            old_admin,
+            vault_state.admin
+        );
+
+        Ok(())
+    }
+
+    /// Assign an operational role to a key (admin-only).
+    ///
+    /// Parameters:
+    /// - role: The role to assign
+    /// - authority: Key that will hold the role
+    ///
+    /// Security assumptions:
+    /// - Only the admin can call this instruction
+    /// - Each role only grants access to its own instructions, so ops keys do not
+    ///   need the admin key
+    pub fn set_role(ctx: Context<SetRole>, role: VaultRole, authority: Pubkey) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        *vault_state.role_mut(role) = authority;
+
+        emit!(RoleUpdatedEvent {
+            vault_state: vault_state.key(),
+            role,
+            authority,
+        });
+
+        msg!("Role {:?} assigned to {}", role, authority);
+
+        Ok(())
+    }
+
+    /// Pause one or more vault operations (pauser-only).
+    ///
+    /// Parameters:
+    /// - flags: Bitmask of PAUSE_DEPOSIT, PAUSE_REQUEST_WITHDRAW and PAUSE_CLAIM to set
+    ///
+    /// Security assumptions:
+    /// - Only the pauser can call this instruction
+    /// - Flags are independent, so deposits can be frozen while users can still exit
+    ///   (or vice versa)
+    pub fn pause(ctx: Context<UpdatePauseFlags>, flags: u8) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        require!(
+            flags != 0 && flags & !PAUSE_ALL == 0,
+            VaultError::InvalidPauseFlags
+        );
+
+        vault_state.paused_flags |= flags;
+
+        emit!(PauseFlagsUpdatedEvent {
+            vault_state: vault_state.key(),
+            paused: true,
+            flags,
+            paused_flags: vault_state.paused_flags,
+        });
+
+        msg!(
+            "Paused operations: flags={:#05b}, paused_flags={:#05b}",
+            flags,
+            vault_state.paused_flags
+        );
+
+        Ok(())
+    }
+
+    /// Unpause one or more vault operations (pauser-only).
+    ///
+    /// Parameters:
+    /// - flags: Bitmask of PAUSE_DEPOSIT, PAUSE_REQUEST_WITHDRAW and PAUSE_CLAIM to clear
+    ///
+    /// Security assumptions:
+    /// - Only the pauser can call this instruction
+    pub fn unpause(ctx: Context<UpdatePauseFlags>, flags: u8) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        require!(
+            flags != 0 && flags & !PAUSE_ALL == 0,
+            VaultError::InvalidPauseFlags
+        );
+
+        vault_state.paused_flags &= !flags;
+
+        emit!(PauseFlagsUpdatedEvent {
+            vault_state: vault_state.key(),
+            paused: false,
+            flags,
+            paused_flags: vault_state.paused_flags,
+        });
+
+        msg!(
+            "Unpaused operations: flags={:#05b}, paused_flags={:#05b}",
+            flags,
+            vault_state.paused_flags
+        );
+
+        Ok(())
+    }
+
+    /// Configure the entry and exit fees and the treasury that receives them (fee-manager-only).
+    ///
+    /// Parameters:
+    /// - entry_fee_bps: Fee taken from each deposit, in basis points
+    /// - exit_fee_bps: Fee taken from each claimed withdrawal, in basis points
+    /// - treasury: Owner of the token accounts that receive fees
+    ///
+    /// Security assumptions:
+    /// - Only the fee manager can call this instruction
+    /// - Both fees are capped at MAX_FEE_BPS, so a compromised fee manager cannot
+    ///   confiscate deposits
+    /// - The treasury must be a real key (not Pubkey::default())
+    /// - The treasury must already own a deposit token account and an IOU token account
+    ///   (passed in and checked for mint and owner), since every fee is routed to them;
+    ///   user instructions fail with TreasuryAccountMissing if a fee is due and they are
+    ///   not supplied
+    pub fn set_fees(
+        ctx: Context<SetFees>,
+        entry_fee_bps: u16,
+        exit_fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        require!(
+            entry_fee_bps <= MAX_FEE_BPS && exit_fee_bps <= MAX_FEE_BPS,
+            VaultError::InvalidFeeConfig
+        );
+        require!(treasury != Pubkey::default(), VaultError::InvalidFeeConfig);
+
+        vault_state.entry_fee_bps = entry_fee_bps;
+        vault_state.exit_fee_bps = exit_fee_bps;
+        vault_state.treasury = treasury;
+
+        emit!(FeesUpdatedEvent {
+            vault_state: vault_state.key(),
+            entry_fee_bps,
+            exit_fee_bps,
+            treasury,
+        });
+
+        msg!(
+            "Fees updated: entry_fee_bps={}, exit_fee_bps={}, treasury={}",
+            entry_fee_bps,
+            exit_fee_bps,
+            treasury
+        );
+
+        Ok(())
+    }
+
+    /// Configure the performance fee charged on exchange rate gains (fee-manager-only).
+    ///
+    /// Parameters:
+    /// - performance_fee_bps: Share of each gain above the high-water mark paid to the
+    ///   treasury, in basis points
+    ///
+    /// Security assumptions:
+    /// - Only the fee manager can call this instruction
+    /// - The fee is capped at MAX_PERFORMANCE_FEE_BPS
+    /// - The high-water mark is left untouched, so changing the fee never charges gains
+    ///   that were already accounted for
+    pub fn set_performance_fee(
+        ctx: Context<SetPerformanceFee>,
+        performance_fee_bps: u16,
+    ) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        require!(
+            performance_fee_bps <= MAX_PERFORMANCE_FEE_BPS,
+            VaultError::InvalidFeeConfig
+        );
+
+        vault_state.performance_fee_bps = performance_fee_bps;
+
+        emit!(PerformanceFeeUpdatedEvent {
+            vault_state: vault_state.key(),
+            performance_fee_bps,
+            high_water_mark: vault_state.high_water_mark,
+        });
+
+        msg!(
+            "Performance fee updated: performance_fee_bps={}, high_water_mark={}",
+            performance_fee_bps,
+            vault_state.high_water_mark
+        );
+
+        Ok(())
+    }
+
+    /// Configure the annual management fee (fee-manager-only).
+    ///
+    /// Parameters:
+    /// - management_fee_bps: Annual fee on vault value, in basis points
+    ///
+    /// Security assumptions:
+    /// - Only the fee manager can call this instruction
+    /// - The fee is capped at MAX_MANAGEMENT_FEE_BPS
+    /// - The fee accrued so far is collected at the old rate first, so the new rate
+    ///   never applies retroactively
+    pub fn set_management_fee(
+        ctx: Context<SetManagementFee>,
+        management_fee_bps: u16,
+    ) -> Result<()> {
+        require!(
+            management_fee_bps <= MAX_MANAGEMENT_FEE_BPS,
+            VaultError::InvalidFeeConfig
+        );
+
+        collect_management_fee(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.iou_mint,
+            Some(&ctx.accounts.treasury_iou_token_account),
+            &mut ctx.accounts.vault_state,
+        )?;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.management_fee_bps = management_fee_bps;
+
+        // Nothing left uncollected at the old rate; the new rate starts now
+        vault_state.last_fee_accrual_ts = Clock::get()?.unix_timestamp;
+
+        emit!(ManagementFeeUpdatedEvent {
+            vault_state: vault_state.key(),
+            management_fee_bps,
+        });
+
+        msg!(
+            "Management fee updated: management_fee_bps={}",
+            management_fee_bps
+        );
+
+        Ok(())
+    }
+
+    /// Collect the management fee accrued since the last collection (permissionless).
+    ///
+    /// Parameters:
+    /// - None
+    ///
+    /// Security assumptions:
+    /// - Anyone can call this instruction; the fee only depends on elapsed time and the
+    ///   configured rate, and is always minted to the vault's treasury
+    /// - Calling it often cannot erase the fee: the accrual clock only advances once a
+    ///   non-zero fee has been minted
+    pub fn crank_fees(ctx: Context<CrankFees>) -> Result<()> {
+        let fee_shares = collect_management_fee(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.iou_mint,
+            Some(&ctx.accounts.treasury_iou_token_account),
+            &mut ctx.accounts.vault_state,
+        )?;
+
+        msg!(
+            "Management fee collected: {} IOU (exchange_rate: {})",
+            fee_shares,
+            ctx.accounts.vault_state.exchange_rate
+        );
+
+        Ok(())
+    }
+
+    /// Migrate a vault state written by an older program version to the current layout
+    /// (admin-only).
+    ///
+    /// Parameters:
+    /// - legacy_pending_iou_amount: Total IOU tokens burned into withdrawal tickets the
+    ///   original program has not paid out yet (claimed = false); they stay outstanding
+    ///   until claim_legacy_withdraw pays each ticket
+    ///
+    /// Security assumptions:
+    /// - Only the admin recorded in the old account can call this instruction
+    /// - legacy_pending_iou_amount is trusted from the admin and should match the unpaid
+    ///   legacy tickets on chain. Legacy claims release it saturating at zero, so too low
+    ///   never blocks a claim and only overvalues IOUs in share-based mode until those
+    ///   tickets are paid; too high undervalues them for good, so when in doubt undercount
+    /// - The account must be the vault_state PDA of deposit_mint, owned by this program;
+    ///   its layout is identified by its size (unversioned legacy layout) or its version,
+    ///   and an account already at VAULT_STATE_VERSION is rejected with AlreadyMigrated,
+    ///   so a vault is never rewritten twice
+    /// - The legacy rate is rescaled from LEGACY_EXCHANGE_RATE_SCALE to EXCHANGE_RATE_SCALE,
+    ///   so every price is unchanged; fields missing from the old layout get the values
+    ///   initialize would set (admin roles and treasury, fees off, epoch and fee clocks
+    ///   starting now, high-water mark and epoch start rate at the current rate)
+    /// - The supplied vault token account (deposit mint, owned by vault_state) is recorded
+    ///   as the vault's canonical token account, so it must be the one holding the assets
+    /// - The admin pays the rent if the account grows
+    pub fn migrate_vault_state(
+        ctx: Context<MigrateVaultState>,
+        legacy_pending_iou_amount: u64,
+    ) -> Result<()> {
+        let vault_info = ctx.accounts.vault_state.to_account_info();
+        let (from_version, vault_state) = {
+            let data = vault_info.try_borrow_data()?;
+            require!(
+                data.starts_with(VaultState::DISCRIMINATOR),
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+
+            // Version 0 predates the version field and is recognized by its size
+            let from_version = if data.len() == LegacyVaultState::LEN {
+                0
+            } else {
+                VaultState::try_deserialize(&mut &data[..])?.version
+            };
+
+            // Bumping VAULT_STATE_VERSION makes this match non-exhaustive until the
+            // previous version gets a migration arm
+            let vault_state = match from_version {
+                0 => LegacyVaultState::deserialize(&mut &data[VaultState::DISCRIMINATOR.len()..])?
+                    .into_current(
+                        ctx.accounts.vault_deposit_token_account.key(),
+                        ctx.bumps.vault_state,
+                        legacy_pending_iou_amount,
+                        &Clock::get()?,
+                    ),
+                VAULT_STATE_VERSION..=u8::MAX => return err!(VaultError::AlreadyMigrated),
+            };
+            (from_version, vault_state)
+        };
+        require_keys_eq!(
+            vault_state.admin,
+            ctx.accounts.admin.key(),
+            VaultError::UnauthorizedAdmin
+        );
+
+        // Fund the rent for the current layout, then resize and rewrite the account
+        let rent = Rent::get()?.minimum_balance(VaultState::LEN);
+        let shortfall = rent.saturating_sub(vault_info.lamports());
+        if shortfall > 0 {
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: vault_info.clone(),
+                },
+            );
+            system_program::transfer(transfer_ctx, shortfall)?;
+        }
+        vault_info.resize(VaultState::LEN)?;
+        {
+            let mut data = vault_info.try_borrow_mut_data()?;
+            let mut writer: &mut [u8] = &mut data;
+            vault_state.try_serialize(&mut writer)?;
+        }
+
+        emit!(VaultStateMigratedEvent {
+            vault_state: vault_info.key(),
+            from_version,
+            to_version: vault_state.version,
+            exchange_rate: vault_state.exchange_rate,
+        });
+
+        msg!(
+            "Vault state migrated from version {} to {} (exchange_rate: {})",
+            from_version,
+            vault_state.version,
+            vault_state.exchange_rate
+        );
+
+        Ok(())
+    }
+
+    /// Claim a withdrawal ticket opened by the original program (one unindexed ticket per
+    /// user and vault) and close it.
+    ///
+    /// Parameters:
+    /// - min_assets_out: Minimum deposit tokens the user accepts (0 = no slippage check)
+    ///
+    /// Security assumptions:
+    /// - The vault must already be migrated: vault_state only deserializes in the current
+    ///   layout
+    /// - The ticket must be the user's legacy PDA (seeded without a ticket index), owned by
+    ///   this program and in the 57-byte legacy layout
+    /// - A ticket the original program already paid out (claimed) is only closed
+    /// - Any other ticket must be unlocked and is priced like an unlocked ticket, at the
+    ///   claim-time rate, without the exit fee, which did not exist when it was requested;
+    ///   it releases the IOUs migrate_vault_state counted into pending_iou_amount, saturating
+    ///   at zero so an undercounted migration cannot lock out the last legacy claimants
+    /// - The accrued management fee is collected before the claim is priced
+    /// - The ticket is closed on success (rent refunded to the user), so it cannot be
+    ///   claimed twice
+    /// - Claims must not be paused
+    pub fn claim_legacy_withdraw(
+        ctx: Context<ClaimLegacyWithdraw>,
+        min_assets_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.vault_state.require_not_paused(PAUSE_CLAIM)?;
+
+        let ticket_info = ctx.accounts.withdrawal_ticket.to_account_info();
+        let withdrawal_ticket = {
+            let data = ticket_info.try_borrow_data()?;
+            require!(
+                data.starts_with(WithdrawalTicket::DISCRIMINATOR),
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+            require!(
+                data.len() == LegacyWithdrawalTicket::LEN,
+                ErrorCode::AccountDidNotDeserialize
+            );
+            LegacyWithdrawalTicket::deserialize(
+                &mut &data[WithdrawalTicket::DISCRIMINATOR.len()..],
+            )?
+        };
+
+        // Validate ticket ownership
+        require!(
+            withdrawal_ticket.user == ctx.accounts.user.key(),
+            VaultError::InvalidTicketOwner
+        );
+
+        collect_management_fee(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.iou_mint,
+            ctx.accounts.treasury_iou_token_account.as_ref(),
+            &mut ctx.accounts.vault_state,
+        )?;
+
+        // Tickets the original program paid out were kept open and flagged; close them
+        // without paying again
+        let mut deposit_amount = 0;
+        let vault_state = &ctx.accounts.vault_state;
+        let exchange_rate = vault_state.current_exchange_rate(
+            ctx.accounts.vault_deposit_token_account.amount,
+            ctx.accounts.iou_mint.supply,
+            Rounding::Down,
+        )?;
+        if !withdrawal_ticket.claimed {
+            require!(
+                vault_state.current_epoch >= withdrawal_ticket.unlock_epoch,
+                VaultError::WithdrawalNotReady
+            );
+
+            deposit_amount = ious_to_assets_floor(withdrawal_ticket.iou_amount, exchange_rate)?;
+            require!(deposit_amount > 0, VaultError::InvalidAmount);
+            require!(
+                deposit_amount >= min_assets_out,
+                VaultError::SlippageExceeded
+            );
+
+            // Assets reserved for locked tickets are only available to those tickets
+            let available_assets = ctx
+                .accounts
+                .vault_deposit_token_account
+                .amount
+                .saturating_sub(vault_state.pending_withdrawal_assets);
+            require!(
+                available_assets >= deposit_amount,
+                VaultError::InsufficientVaultBalance
+            );
+
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                b"vault_state",
+                vault_state.deposit_mint.as_ref(),
+                &[vault_state.bump],
+            ]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    mint: ctx.accounts.deposit_mint.to_account_info(),
+                    from: ctx.accounts.vault_deposit_token_account.to_account_info(),
+                    to: ctx.accounts.user_deposit_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_state.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token_interface::transfer_checked(
+                transfer_ctx,
+                deposit_amount,
+                ctx.accounts.deposit_mint.decimals,
+            )?;
+
+            // The legacy count is admin-supplied, so running out of it must not fail the claim
+            let vault_state = &mut ctx.accounts.vault_state;
+            vault_state.exchange_rate = exchange_rate;
+            vault_state.pending_iou_amount = vault_state
+                .pending_iou_amount
+                .saturating_sub(withdrawal_ticket.iou_amount);
+        }
+
+        // Close the ticket by hand (it is not an Account<WithdrawalTicket>), refunding
+        // its rent to the user
+        let user_info = ctx.accounts.user.to_account_info();
+        let refund = ticket_info.lamports();
+        **user_info.try_borrow_mut_lamports()? = user_info
+            .lamports()
+            .checked_add(refund)
+            .ok_or(VaultError::MathOverflow)?;
+        **ticket_info.try_borrow_mut_lamports()? = 0;
+        ticket_info.assign(&system_program::ID);
+        ticket_info.resize(0)?;
+
+        let vault_state = &ctx.accounts.vault_state;
+        emit!(LegacyWithdrawClaimedEvent {
+            vault_state: vault_state.key(),
+            user: ctx.accounts.user.key(),
+            iou_amount: withdrawal_ticket.iou_amount,
+            deposit_amount,
+            exchange_rate,
+            epoch: vault_state.current_epoch,
+        });
+
+        msg!(
+            "Claimed legacy withdrawal: {} deposit tokens transferred (iou_amount: {}, already claimed: {}, exchange_rate: {})",
+            deposit_amount,
+            withdrawal_ticket.iou_amount,
+            withdrawal_ticket.claimed,
+            exchange_rate
+        );
+
+        Ok(())
+    }
+}
+
+/// Charge the performance fee on the exchange rate gain above the high-water mark and
+/// mint it to the treasury. The treasury's IOU token account is only required when a fee
+/// is due. Returns the IOU tokens minted.
+fn collect_performance_fee<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    iou_mint: &InterfaceAccount<'info, Mint>,
+    treasury_iou_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    vault_state: &mut Account<'info, VaultState>,
+) -> Result<u64> {
+    let fee_shares = vault_state.accrue_performance_fee(iou_mint.supply)?;
+    if fee_shares == 0 {
+        return Ok(0);
+    }
+    let treasury_iou_token_account =
+        treasury_iou_token_account.ok_or(VaultError::TreasuryAccountMissing)?;
+
+    mint_fee_shares(
+        token_program,
+        iou_mint,
+        treasury_iou_token_account,
+        vault_state,
+        fee_shares,
+    )?;
+
+    emit!(PerformanceFeeAccruedEvent {
+        vault_state: vault_state.key(),
+        fee_shares,
+        exchange_rate: vault_state.exchange_rate,
+        high_water_mark: vault_state.high_water_mark,
+    });
+
+    Ok(fee_shares)
+}
+
+/// Charge the management fee accrued since last_fee_accrual_ts and mint it to the
+/// treasury. The treasury's IOU token account is only required when a fee is due. The
+/// IOU mint is reloaded so callers price against the diluted supply.
+/// Returns the IOU tokens minted.
+fn collect_management_fee<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    iou_mint: &mut InterfaceAccount<'info, Mint>,
+    treasury_iou_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    vault_state: &mut Account<'info, VaultState>,
+) -> Result<u64> {
+    let now = Clock::get()?.unix_timestamp;
+    let fee_shares = vault_state.accrue_management_fee(iou_mint.supply, now)?;
+    if fee_shares == 0 {
+        return Ok(0);
+    }
+    let treasury_iou_token_account =
+        treasury_iou_token_account.ok_or(VaultError::TreasuryAccountMissing)?;
+
+    mint_fee_shares(
+        token_program,
+        iou_mint,
+        treasury_iou_token_account,
+        vault_state,
+        fee_shares,
+    )?;
+    iou_mint.reload()?;
+
+    emit!(ManagementFeeAccruedEvent {
+        vault_state: vault_state.key(),
+        fee_shares,
+        exchange_rate: vault_state.exchange_rate,
+        timestamp: now,
+    });
+
+    Ok(fee_shares)
+}
+
+/// Mint `fee_shares` IOU tokens to the treasury, signed by the vault_state PDA.
+fn mint_fee_shares<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    iou_mint: &InterfaceAccount<'info, Mint>,
+    treasury_iou_token_account: &InterfaceAccount<'info, TokenAccount>,
+    vault_state: &Account<'info, VaultState>,
+    fee_shares: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault_state",
+        vault_state.deposit_mint.as_ref(),
+        &[vault_state.bump],
+    ]];
+    let mint_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        MintTo {
+            mint: iou_mint.to_account_info(),
+            to: treasury_iou_token_account.to_account_info(),
+            authority: vault_state.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token_interface::mint_to(mint_ctx, fee_shares)
+}
+
+/// Burn `iou_amount` IOU tokens from the user and open a withdrawal ticket at the user's
+/// next ticket index, locking its value if lock_withdrawal_value is set. Shared by
+/// request_withdraw and request_withdraw_assets; the management fee must already be
+/// collected.
+fn open_withdrawal_ticket(
+    accounts: &mut RequestWithdraw,
+    ticket_bump: u8,
+    iou_amount: u64,
+) -> Result<()> {
+    // Small requests are rejected unless they redeem the user's whole balance
+    require!(
+        iou_amount >= accounts.vault_state.min_redeem_iou_amount
+            || iou_amount == accounts.user_iou_token_account.amount,
+        VaultError::RedeemBelowMinimum
+    );
+
+    // Price the ticket now if its value is locked at request time
+    // (the rate is resolved before the burn so the IOUs still count as supply)
+    let locked_deposit_amount = if accounts.vault_state.lock_withdrawal_value {
+        let exchange_rate = accounts.vault_state.current_exchange_rate(
+            accounts.vault_deposit_token_account.amount,
+            accounts.iou_mint.supply,
+            Rounding::Down,
+        )?;
+        require!(exchange_rate > 0, VaultError::InvalidExchangeRate);
+        accounts.vault_state.exchange_rate = exchange_rate;
+
+        let deposit_amount = ious_to_assets_floor(iou_amount, exchange_rate)?;
+        require!(deposit_amount > 0, VaultError::RedeemBelowMinimum);
+        deposit_amount
+    } else {
+        0
+    };
+
+    let vault_state = &mut accounts.vault_state;
+
+    let user_position = &mut accounts.user_position;
+    user_position.init_if_empty(accounts.user.key(), vault_state.key());
+
+    // Burn IOU tokens from user's account
+    let iou_mint_decimals = accounts.iou_mint.decimals;
+    let burn_ctx = CpiContext::new(
+        accounts.token_program.to_account_info(),
+        BurnChecked {
+            mint: accounts.iou_mint.to_account_info(),
+            from: accounts.user_iou_token_account.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        },
+    );
+    token_interface::burn_checked(burn_ctx, iou_amount, iou_mint_decimals)?;
+
+    // Create withdrawal ticket with unlock_epoch = current_epoch + 1
+    let unlock_epoch = vault_state
+        .current_epoch
+        .checked_add(1)
+        .ok_or(VaultError::MathOverflow)?;
+
+    // The ticket PDA was derived from the user's current next_ticket_index
+    let ticket_index = user_position.next_ticket_index;
+
+    let withdrawal_ticket = &mut accounts.withdrawal_ticket;
+    withdrawal_ticket.user = accounts.user.key();
+    withdrawal_ticket.ticket_index = ticket_index;
+    withdrawal_ticket.iou_amount = iou_amount;
+    withdrawal_ticket.unlock_epoch = unlock_epoch;
+    withdrawal_ticket.locked_deposit_amount = locked_deposit_amount;
+    withdrawal_ticket.bump = ticket_bump;
+
+    if locked_deposit_amount > 0 {
+        // Earmark the owed assets; the ticket no longer holds a share of the vault
+        vault_state.pending_withdrawal_assets = vault_state
+            .pending_withdrawal_assets
+            .checked_add(locked_deposit_amount)
+            .ok_or(VaultError::MathOverflow)?;
+    } else {
+        // Burned IOUs of pending tickets still count as outstanding shares
+        vault_state.pending_iou_amount = vault_state
+            .pending_iou_amount
+            .checked_add(iou_amount)
+            .ok_or(VaultError::MathOverflow)?;
+    }
+
+    // Advance the user's ticket counter so the next request gets a fresh PDA
+    user_position.next_ticket_index = ticket_index
+        .checked_add(1)
+        .ok_or(VaultError::MathOverflow)?;
+    user_position.open_tickets = user_position
+        .open_tickets
+        .checked_add(1)
+        .ok_or(VaultError::MathOverflow)?;
+
+    emit!(WithdrawRequestedEvent {
+        vault_state: vault_state.key(),
+        user: accounts.user.key(),
+        ticket_index,
+        iou_amount,
+        locked_deposit_amount,
+        exchange_rate: vault_state.exchange_rate,
+        epoch: vault_state.current_epoch,
+        unlock_epoch,
+    });
+
+    msg!(
+        "Requested withdrawal: {} IOU tokens burned, ticket_index: {}, unlock_epoch: {}, locked_deposit_amount: {}",
+        iou_amount,
+        ticket_index,
+        unlock_epoch,
+        locked_deposit_amount
+    );
+
+    Ok(())
+}
+
+/// Collect the management fee and resolve the exchange rate a deposit is priced at,
+/// recording it on the vault if it was derived. Shared by deposit and mint_shares.
+fn price_deposit(accounts: &mut Deposit) -> Result<u128> {
+    collect_management_fee(
+        &accounts.token_program,
+        &mut accounts.iou_mint,
+        accounts.treasury_iou_token_account.as_ref(),
+        &mut accounts.vault_state,
+    )?;
+
+    // A derived rate is rounded up, so the depositor never buys IOUs below their value
+    let exchange_rate = accounts.vault_state.current_exchange_rate(
+        accounts.vault_deposit_token_account.amount,
+        accounts.iou_mint.supply,
+        Rounding::Up,
+    )?;
+    accounts.vault_state.exchange_rate = exchange_rate;
+
+    // Ensure exchange rate is set
+    require!(exchange_rate > 0, VaultError::InvalidExchangeRate);
+    Ok(exchange_rate)
+}
+
+/// Enforce the minimum and caps on a priced deposit, move the deposit tokens (the entry
+/// fee to the treasury, the rest to the vault) and mint `iou_amount` IOU tokens to the
+/// user. Shared by deposit and mint_shares.
+fn settle_deposit(
+    accounts: &mut Deposit,
+    deposit_amount: u64,
+    fee_amount: u64,
+    iou_amount: u64,
+    exchange_rate: u128,
+) -> Result<()> {
+    let vault_state = &accounts.vault_state;
+    require!(
+        deposit_amount >= vault_state.min_deposit_amount,
+        VaultError::DepositBelowMinimum
+    );
+    let net_deposit_amount = deposit_amount
+        .checked_sub(fee_amount)
+        .ok_or(VaultError::MathOverflow)?;
+
+    // Enforce the vault-wide cap on managed assets (reserved assets are already owed out)
+    if vault_state.max_total_assets > 0 {
+        let total_assets = accounts
+            .vault_deposit_token_account
+            .amount
+            .saturating_sub(vault_state.pending_withdrawal_assets)
+            .checked_add(net_deposit_amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(
+            total_assets <= vault_state.max_total_assets,
+            VaultError::DepositCapExceeded
+        );
+    }
+
+    let user_position = &mut accounts.user_position;
+    user_position.init_if_empty(accounts.user.key(), vault_state.key());
+
+    // Enforce the per-user cap on the user's outstanding deposits
+    let total_deposited = user_position
+        .total_deposited
+        .checked_add(net_deposit_amount)
+        .ok_or(VaultError::MathOverflow)?;
+    if vault_state.max_deposit_per_user > 0 {
+        require!(
+            total_deposited <= vault_state.max_deposit_per_user,
+            VaultError::UserDepositCapExceeded
+        );
+    }
+    user_position.total_deposited = total_deposited;
+
+    // Transfer deposit tokens from user to vault
+    let deposit_mint_decimals = accounts.deposit_mint.decimals;
+    let transfer_ctx = CpiContext::new(
+        accounts.token_program.to_account_info(),
+        TransferChecked {
+            mint: accounts.deposit_mint.to_account_info(),
+            from: accounts.user_deposit_token_account.to_account_info(),
+            to: accounts.vault_deposit_token_account.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        },
+    );
+    token_interface::transfer_checked(transfer_ctx, net_deposit_amount, deposit_mint_decimals)?;
+
+    // Transfer the entry fee from user to treasury
+    if fee_amount > 0 {
+        let treasury_deposit_token_account = accounts
+            .treasury_deposit_token_account
+            .as_ref()
+            .ok_or(VaultError::TreasuryAccountMissing)?;
+        let fee_ctx = CpiContext::new(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                mint: accounts.deposit_mint.to_account_info(),
+                from: accounts.user_deposit_token_account.to_account_info(),
+                to: treasury_deposit_token_account.to_account_info(),
+                authority: accounts.user.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(fee_ctx, fee_amount, deposit_mint_decimals)?;
+    }
+
+    // Mint IOU tokens to user
+    // The vault_state PDA must be the mint authority for the IOU mint
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault_state",
+        vault_state.deposit_mint.as_ref(),
+        &[vault_state.bump],
+    ]];
+    let mint_ctx = CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        MintTo {
+            mint: accounts.iou_mint.to_account_info(),
+            to: accounts.user_iou_token_account.to_account_info(),
+            authority: accounts.vault_state.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token_interface::mint_to(mint_ctx, iou_amount)?;
+
+    emit!(DepositEvent {
+        vault_state: vault_state.key(),
+        user: accounts.user.key(),
+        deposit_amount,
+        fee_amount,
+        iou_amount,
+        exchange_rate,
+        epoch: vault_state.current_epoch,
+    });
+
+    msg!(
+        "Deposited {} deposit tokens (fee: {}), received {} IOU tokens (exchange_rate: {})",
+        deposit_amount,
+        fee_amount,
+        iou_amount,
+        exchange_rate
+    );
+
+    Ok(())
+}
+
+/// Operational roles that the admin can delegate with set_role.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VaultRole {
+    /// Can raise the exchange rate and co-sign losses
+    RateManager,
+    /// Can deposit yield into the vault
+    YieldDepositor,
+    /// Can pause and unpause vault operations
+    Pauser,
+    /// Can configure vault fees
+    FeeManager,
+    /// Can advance epochs
+    EpochAuthority,
+}
+
+impl VaultRole {
+    /// Every role, in declaration order
+    pub const ALL: [VaultRole; 5] = [
+        VaultRole::RateManager,
+        VaultRole::YieldDepositor,
+        VaultRole::Pauser,
+        VaultRole::FeeManager,
+        VaultRole::EpochAuthority,
+    ];
+}
+
+/// VaultState stores the global vault configuration and state.
+/// This is a PDA derived from the deposit_mint to ensure one vault per deposit token type.
+#[account]
+pub struct VaultState {
+    /// Admin authority that configures the vault and assigns roles
+    pub admin: Pubkey,
+    /// The mint of tokens that can be deposited into the vault
+    pub deposit_mint: Pubkey,
+    /// The mint of IOU tokens representing shares in the vault
+    pub iou_mint: Pubkey,
+    /// Exchange rate: iou_amount = deposit_amount * EXCHANGE_RATE_SCALE / exchange_rate
+    /// When exchange_rate increases, IOU becomes more valuable (yield-bearing behavior)
+    /// Scaled by EXCHANGE_RATE_SCALE (1e18) for precision
+    /// Example: exchange_rate = 1_100_000_000_000_000_000 means 1 IOU = 1.1 tokens
+    pub exchange_rate: u128,
+    /// Current epoch number (incremented by the epoch authority via advance_epoch)
+    pub current_epoch: u64,
+    /// When true, exchange_rate is derived from vault assets / outstanding IOUs
+    /// (ERC-4626 style) instead of being set by the admin
+    pub share_based_accounting: bool,
+    /// IOU tokens burned into unclaimed withdrawal tickets whose value is not locked
+    pub pending_iou_amount: u64,
+    /// When true, request_withdraw snapshots the owed deposit amount into the ticket
+    pub lock_withdrawal_value: bool,
+    /// Deposit tokens owed to unclaimed tickets with a locked value (earmarked liquidity)
+    pub pending_withdrawal_assets: u64,
+    /// Role that can advance epochs
+    pub epoch_authority: Pubkey,
+    /// Minimum number of slots between epoch advances
+    pub min_epoch_slots: u64,
+    /// Minimum number of seconds between epoch advances
+    pub min_epoch_seconds: i64,
+    /// Slot at which the current epoch started
+    pub last_epoch_slot: u64,
+    /// Unix timestamp at which the current epoch started
+    pub last_epoch_timestamp: i64,
+    /// Maximum increase of the exchange rate per epoch, in basis points
+    pub max_rate_increase_bps: u16,
+    /// Exchange rate at the start of the current epoch (base for max_rate_increase_bps)
+    pub epoch_start_rate: u128,
+    /// Authority that must co-sign apply_loss (Pubkey::default() disables losses)
+    pub loss_authority: Pubkey,
+    /// Admin proposed via propose_admin, pending acceptance (Pubkey::default() if none)
+    pub pending_admin: Pubkey,
+    /// Role that can raise the exchange rate and co-sign losses
+    pub rate_manager: Pubkey,
+    /// Role that can deposit yield into the vault
+    pub yield_depositor: Pubkey,
+    /// Role that can pause and unpause vault operations
+    pub pauser: Pubkey,
+    /// Role that can configure vault fees
+    pub fee_manager: Pubkey,
+    /// Bitmask of paused operations (PAUSE_DEPOSIT | PAUSE_REQUEST_WITHDRAW | PAUSE_CLAIM)
+    pub paused_flags: u8,
+    /// Owner of the token accounts that receive fees
+    pub treasury: Pubkey,
+    /// Fee taken from each deposit, in basis points (at most MAX_FEE_BPS)
+    pub entry_fee_bps: u16,
+    /// Fee taken from each claimed withdrawal, in basis points (at most MAX_FEE_BPS)
+    pub exit_fee_bps: u16,
+    /// Share of exchange rate gains above high_water_mark paid to the treasury, in basis
+    /// points (at most MAX_PERFORMANCE_FEE_BPS)
+    pub performance_fee_bps: u16,
+    /// Highest post-fee exchange rate reached so far; performance fees are only charged above it
+    pub high_water_mark: u128,
+    /// Annual fee on vault value paid to the treasury, in basis points (at most
+    /// MAX_MANAGEMENT_FEE_BPS)
+    pub management_fee_bps: u16,
+    /// Unix timestamp up to which the management fee has been collected
+    pub last_fee_accrual_ts: i64,
+    /// Maximum deposit tokens managed by the vault (0 = unlimited)
+    pub max_total_assets: u64,
+    /// Maximum outstanding deposits per user position (0 = unlimited)
+    pub max_deposit_per_user: u64,
+    /// Smallest deposit accepted, in deposit tokens (0 = no minimum)
+    pub min_deposit_amount: u64,
+    /// Smallest withdrawal request accepted, in IOU tokens (0 = no minimum)
+    pub min_redeem_iou_amount: u64,
+    /// The vault's deposit token account (the vault_state's associated token account
+    /// for vaults created by initialize); the only account the vault holds assets in
+    pub vault_token_account: Pubkey,
+    /// Canonical bump of the vault_state PDA, stored at creation so contexts and signer
+    /// seeds do not re-derive it
+    pub bump: u8,
+    /// Layout version (VAULT_STATE_VERSION); unversioned legacy accounts count as 0
+    pub version: u8,
+    /// Zeroed space for future fields, so adding one does not require a realloc
+    pub reserved: [u8; 128],
+}
+
+impl VaultState {
+    /// Account space, field by field in declaration order
+    pub const LEN: usize = 8 // discriminator
+        + 32 // admin
+        + 32 // deposit_mint
+        + 32 // iou_mint
+        + 16 // exchange_rate
+        + 8 // current_epoch
+        + 1 // share_based_accounting
+        + 8 // pending_iou_amount
+        + 1 // lock_withdrawal_value
+        + 8 // pending_withdrawal_assets
+        + 32 // epoch_authority
+        + 8 // min_epoch_slots
+        + 8 // min_epoch_seconds
+        + 8 // last_epoch_slot
+        + 8 // last_epoch_timestamp
+        + 2 // max_rate_increase_bps
+        + 16 // epoch_start_rate
+        + 32 // loss_authority
+        + 32 // pending_admin
+        + 32 // rate_manager
+        + 32 // yield_depositor
+        + 32 // pauser
+        + 32 // fee_manager
+        + 1 // paused_flags
+        + 32 // treasury
+        + 2 // entry_fee_bps
+        + 2 // exit_fee_bps
+        + 2 // performance_fee_bps
+        + 16 // high_water_mark
+        + 2 // management_fee_bps
+        + 8 // last_fee_accrual_ts
+        + 8 // max_total_assets
+        + 8 // max_deposit_per_user
+        + 8 // min_deposit_amount
+        + 8 // min_redeem_iou_amount
+        + 32 // vault_token_account
+        + 1 // bump
+        + 1 // version
+        + 128; // reserved
+
+    /// Fails with VaultError::Paused if the operation behind `flag` is paused.
+    pub fn require_not_paused(&self, flag: u8) -> Result<()> {
+        require!(self.paused_flags & flag == 0, VaultError::Paused);
+        Ok(())
+    }
+
+    /// Key holding `role`.
+    pub fn role_mut(&mut self, role: VaultRole) -> &mut Pubkey {
+        match role {
+            VaultRole::RateManager => &mut self.rate_manager,
+            VaultRole::YieldDepositor => &mut self.yield_depositor,
+            VaultRole::Pauser => &mut self.pauser,
+            VaultRole::FeeManager => &mut self.fee_manager,
+            VaultRole::EpochAuthority => &mut self.epoch_authority,
+        }
+    }
+
+    /// Makes `new_admin` the admin and clears the pending proposal. Every role, the
+    /// treasury and the loss authority still held by the old admin move along with it;
+    /// keys delegated elsewhere are kept. Returns the roles that moved.
+    pub fn transfer_admin(&mut self, new_admin: Pubkey) -> Vec<VaultRole> {
+        let old_admin = self.admin;
+        let mut moved_roles = Vec::new();
+        for role in VaultRole::ALL {
+            let holder = self.role_mut(role);
+            if *holder == old_admin {
+                *holder = new_admin;
+                moved_roles.push(role);
+            }
+        }
+        for holder in [&mut self.treasury, &mut self.loss_authority] {
+            if *holder == old_admin {
+                *holder = new_admin;
+            }
+        }
+
+        self.admin = new_admin;
+        self.pending_admin = Pubkey::default();
+        moved_roles
+    }
+
+    /// Entry fee owed on a deposit of `deposit_amount`.
+    pub fn entry_fee(&self, deposit_amount: u64) -> Result<u64> {
+        Self::fee_amount(deposit_amount, self.entry_fee_bps)
+    }
+
+    /// Exit fee owed on a withdrawal redeeming `deposit_amount`.
+    pub fn exit_fee(&self, deposit_amount: u64) -> Result<u64> {
+        Self::fee_amount(deposit_amount, self.exit_fee_bps)
+    }
+
+    /// Smallest deposit whose remainder after the entry fee is at least `net_amount`.
+    pub fn gross_up_entry_fee(&self, net_amount: u64) -> Result<u64> {
+        let kept_bps = BPS_DENOMINATOR
+            .checked_sub(u64::from(self.entry_fee_bps))
+            .ok_or(VaultError::MathOverflow)?;
+        mul_div_ceil(net_amount, BPS_DENOMINATOR, kept_bps)
+    }
+
+    fn fee_amount(amount: u64, fee_bps: u16) -> Result<u64> {
+        mul_div_floor(amount, u64::from(fee_bps), BPS_DENOMINATOR)
+    }
+
+    /// Takes the performance fee on the gain of the exchange rate above the high-water mark.
+    /// The fee is paid in IOU tokens: the rate is lowered by the fee per share and the
+    /// returned number of IOU tokens (worth exactly the fee at the lowered rate) must be
+    /// minted to the treasury, so holders keep the rest of the gain. The post-fee rate
+    /// becomes the new high-water mark, also while the fee is zero.
+    pub fn accrue_performance_fee(&mut self, iou_supply: u64) -> Result<u64> {
+        if self.exchange_rate <= self.high_water_mark {
+            return Ok(0);
+        }
+
+        // Pending unlocked tickets are still owed their share, so they pay the fee too
+        let total_shares = iou_supply
+            .checked_add(self.pending_iou_amount)
+            .ok_or(VaultError::MathOverflow)?;
+        let gain_per_share = self
+            .exchange_rate
+            .checked_sub(self.high_water_mark)
+            .ok_or(VaultError::MathOverflow)?;
+        let fee_per_share = mul_div_floor_u128(
+            gain_per_share,
+            u128::from(self.performance_fee_bps),
+            u128::from(BPS_DENOMINATOR),
+        )?;
+
+        let mut fee_shares = 0;
+        if fee_per_share > 0 && total_shares > 0 {
+            let post_fee_rate = self
+                .exchange_rate
+                .checked_sub(fee_per_share)
+                .ok_or(VaultError::MathOverflow)?;
+            fee_shares = to_u64(mul_div_floor_u128(
+                u128::from(total_shares),
+                fee_per_share,
+                post_fee_rate,
+            )?)?;
+
+            // Only lower the rate if the fee is actually paid out
+            if fee_shares > 0 {
+                self.exchange_rate = post_fee_rate;
+            }
+        }
+
+        self.high_water_mark = self.exchange_rate;
+        Ok(fee_shares)
+    }
+
+    /// Takes the management fee accrued between last_fee_accrual_ts and `now`.
+    /// The fee is paid in IOU tokens: the returned amount (management_fee_bps per year of
+    /// the outstanding shares, pro rata) must be minted to the treasury, and the rate is
+    /// lowered so the vault's total value is unchanged by the dilution. The accrual clock
+    /// only advances when a non-zero fee is returned, so frequent calls cannot round the
+    /// fee away.
+    pub fn accrue_management_fee(&mut self, iou_supply: u64, now: i64) -> Result<u64> {
+        if now <= self.last_fee_accrual_ts {
+            return Ok(0);
+        }
+
+        let total_shares = iou_supply
+            .checked_add(self.pending_iou_amount)
+            .ok_or(VaultError::MathOverflow)?;
+        if self.management_fee_bps == 0 || total_shares == 0 {
+            // Nothing to charge for this period
+            self.last_fee_accrual_ts = now;
+            return Ok(0);
+        }
+
+        // Computed in u128: shares * bps * seconds quickly exceeds u64
+        let elapsed =
+            u64::try_from(now - self.last_fee_accrual_ts).map_err(|_| VaultError::MathOverflow)?;
+        let fee_shares = to_u64(mul_div_floor_u128(
+            u128::from(total_shares) * u128::from(self.management_fee_bps),
+            u128::from(elapsed),
+            u128::from(BPS_DENOMINATOR) * u128::from(SECONDS_PER_YEAR),
+        )?)?;
+        if fee_shares == 0 {
+            return Ok(0);
+        }
+
+        // Spread the same value over the diluted share count
+        let diluted_shares = total_shares
+            .checked_add(fee_shares)
+            .ok_or(VaultError::MathOverflow)?;
+        self.exchange_rate = mul_div_floor_u128(
+            self.exchange_rate,
+            u128::from(total_shares),
+            u128::from(diluted_shares),
+        )?;
+        self.last_fee_accrual_ts = now;
+
+        Ok(fee_shares)
+    }
+
+    /// Highest exchange rate increase_rate may set during the current epoch.
+    pub fn max_exchange_rate(&self) -> Result<u128> {
+        let max_increase = mul_div_floor_u128(
+            self.epoch_start_rate,
+            u128::from(self.max_rate_increase_bps),
+            u128::from(BPS_DENOMINATOR),
+        )?;
+        let max_exchange_rate = self
+            .epoch_start_rate
+            .checked_add(max_increase)
+            .ok_or(VaultError::MathOverflow)?;
+        Ok(max_exchange_rate)
+    }
+
+    /// Exchange rate backed by the vault's holdings: total assets / outstanding shares,
+    /// both offset by one virtual unit (see math::share_rate), rounded as requested.
+    /// Outstanding shares include IOUs burned into unlocked pending tickets, since those
+    /// tickets are still owed their share of the assets. Assets reserved for locked tickets
+    /// are excluded. Falls back to the stored rate while no shares are outstanding.
+    pub fn share_exchange_rate(
+        &self,
+        vault_assets: u64,
+        iou_supply: u64,
+        rounding: Rounding,
+    ) -> Result<u128> {
+        let total_shares = iou_supply
+            .checked_add(self.pending_iou_amount)
+            .ok_or(VaultError::MathOverflow)?;
+        if total_shares == 0 {
+            return Ok(self.exchange_rate);
+        }
+
+        // Reserved assets can only exceed the balance after a manual-mode shortfall
+        let vault_assets = vault_assets.saturating_sub(self.pending_withdrawal_assets);
+        share_rate(vault_assets, total_shares, rounding)
+    }
+
+    /// Exchange rate used to price deposits and withdrawals under the configured mode.
+    /// `rounding` only applies to a derived rate: Up for deposits, Down for withdrawals.
+    pub fn current_exchange_rate(
+        &self,
+        vault_assets: u64,
+        iou_supply: u64,
+        rounding: Rounding,
+    ) -> Result<u128> {
+        if self.share_based_accounting {
+            self.share_exchange_rate(vault_assets, iou_supply, rounding)
+        } else {
+            Ok(self.exchange_rate)
+        }
+    }
+}
+
+/// Unversioned VaultState layout (version 0) of vaults created by the original program,
+/// which stored a u64 exchange rate at LEGACY_EXCHANGE_RATE_SCALE and nothing after the
+/// epoch counter. Only read by migrate_vault_state.
+#[derive(AnchorDeserialize)]
+#[cfg_attr(test, derive(AnchorSerialize))]
+struct LegacyVaultState {
+    admin: Pubkey,
+    deposit_mint: Pubkey,
+    iou_mint: Pubkey,
+    exchange_rate: u64,
+    current_epoch: u64,
+}
+
+impl LegacyVaultState {
+    /// Account space, field by field in declaration order
+    const LEN: usize = 8 // discriminator
+        + 32 // admin
+        + 32 // deposit_mint
+        + 32 // iou_mint
+        + 8 // exchange_rate
+        + 8; // current_epoch
+
+    /// Converts to the current layout. The rate is rescaled from
+    /// LEGACY_EXCHANGE_RATE_SCALE to EXCHANGE_RATE_SCALE, so every price is unchanged,
+    /// `vault_token_account` becomes the vault's canonical deposit token account and
+    /// `pending_iou_amount` counts the IOUs of unpaid legacy tickets. Every other field
+    /// gets the value initialize gives a new vault, anchored at the current rate and at
+    /// `clock`.
+    fn into_current(
+        self,
+        vault_token_account: Pubkey,
+        bump: u8,
+        pending_iou_amount: u64,
+        clock: &Clock,
+    ) -> VaultState {
+        let exchange_rate =
+            u128::from(self.exchange_rate) * (EXCHANGE_RATE_SCALE / LEGACY_EXCHANGE_RATE_SCALE);
+        VaultState {
+            admin: self.admin,
+            deposit_mint: self.deposit_mint,
+            iou_mint: self.iou_mint,
+            exchange_rate,
+            current_epoch: self.current_epoch,
+            share_based_accounting: false,
+            pending_iou_amount,
+            lock_withdrawal_value: false,
+            pending_withdrawal_assets: 0,
+            epoch_authority: self.admin,
+            min_epoch_slots: DEFAULT_MIN_EPOCH_SLOTS,
+            min_epoch_seconds: DEFAULT_MIN_EPOCH_SECONDS,
+            last_epoch_slot: clock.slot,
+            last_epoch_timestamp: clock.unix_timestamp,
+            max_rate_increase_bps: DEFAULT_MAX_RATE_INCREASE_BPS,
+            epoch_start_rate: exchange_rate,
+            loss_authority: Pubkey::default(),
+            pending_admin: Pubkey::default(),
+            rate_manager: self.admin,
+            yield_depositor: self.admin,
+            pauser: self.admin,
+            fee_manager: self.admin,
+            paused_flags: 0,
+            treasury: self.admin,
+            entry_fee_bps: 0,
+            exit_fee_bps: 0,
+            performance_fee_bps: 0,
+            high_water_mark: exchange_rate,
+            management_fee_bps: 0,
+            last_fee_accrual_ts: clock.unix_timestamp,
+            max_total_assets: 0,
+            max_deposit_per_user: 0,
+            min_deposit_amount: 0,
+            min_redeem_iou_amount: 0,
+            vault_token_account,
+            bump,
+            version: VAULT_STATE_VERSION,
+            reserved: [0; 128],
+        }
+    }
+}
+
+/// WithdrawalTicket layout of the original program: one ticket per user and vault, seeded
+/// without a ticket index and flagged as claimed instead of being closed. Only read by
+/// claim_legacy_withdraw.
+#[derive(AnchorDeserialize)]
+struct LegacyWithdrawalTicket {
+    user: Pubkey,
+    iou_amount: u64,
+    unlock_epoch: u64,
+    claimed: bool,
+}
+
+impl LegacyWithdrawalTicket {
+    /// Account space, field by field in declaration order
+    const LEN: usize = 8 // discriminator
+        + 32 // user
+        + 8 // iou_amount
+        + 8 // unlock_epoch
+        + 1; // claimed
+}
+
+/// WithdrawalTicket represents a pending withdrawal request.
+/// Users must wait until unlock_epoch before claiming their withdrawal.
+/// The ticket is created by request_withdraw and closed by claim_withdraw.
+#[account]
+pub struct WithdrawalTicket {
+    /// The user who requested the withdrawal
+    pub user: Pubkey,
+    /// Index of this ticket within the user's position (used in the PDA seeds)
+    pub ticket_index: u64,
+    /// Amount of IOU tokens that were burned for this withdrawal
+    pub iou_amount: u64,
+    /// Epoch when the withdrawal can be claimed (current_epoch + 1 when created)
+    pub unlock_epoch: u64,
+    /// Deposit tokens owed, fixed at request time (0 when priced at the claim-time rate)
+    pub locked_deposit_amount: u64,
+    /// Canonical bump of the ticket PDA, stored at creation
+    pub bump: u8,
+}
+
+impl WithdrawalTicket {
+    /// Account space, field by field in declaration order
+    pub const LEN: usize = 8 // discriminator
+        + 32 // user
+        + 8 // ticket_index
+        + 8 // iou_amount
+        + 8 // unlock_epoch
+        + 8 // locked_deposit_amount
+        + 1; // bump
+}
+
+/// UserPosition tracks a user's deposits and withdrawal tickets for a single vault.
+/// This is a PDA derived from the user and vault_state; its counter feeds the ticket PDA seeds.
+#[account]
+pub struct UserPosition {
+    /// The user this position belongs to
+    pub user: Pubkey,
+    /// The vault this position belongs to
+    pub vault_state: Pubkey,
+    /// Index assigned to the user's next withdrawal ticket
+    pub next_ticket_index: u64,
+    /// Number of withdrawal tickets requested but not yet claimed
+    pub open_tickets: u64,
+    /// Deposit tokens invested by the user, less what the user has claimed
+    /// (counted against max_deposit_per_user)
+    pub total_deposited: u64,
+}
+
+impl UserPosition {
+    /// Account space, field by field in declaration order
+    pub const LEN: usize = 8 // discriminator
+        + 32 // user
+        + 32 // vault_state
+        + 8 // next_ticket_index
+        + 8 // open_tickets
+        + 8; // total_deposited
+
+    /// Records the owner of a position created by init_if_needed, which leaves it zeroed.
+    /// Does nothing for a position that is already in use.
+    pub fn init_if_empty(&mut self, user: Pubkey, vault_state: Pubkey) {
+        if self.user == Pubkey::default() {
+            self.user = user;
+            self.vault_state = vault_state;
+        }
+    }
+}
+
+/// Emitted when a vault is initialized.
+#[event]
+pub struct VaultInitializedEvent {
+    /// The new vault
+    pub vault_state: Pubkey,
+    /// Admin authority of the vault
+    pub admin: Pubkey,
+    /// Mint of the deposit token
+    pub deposit_mint: Pubkey,
+    /// Mint of the IOU token
+    pub iou_mint: Pubkey,
+    /// Token account holding the vault's deposit tokens
+    pub vault_token_account: Pubkey,
+    /// Initial exchange rate
+    pub exchange_rate: u128,
+    /// Initial epoch
+    pub epoch: u64,
+}
+
+/// Emitted when a user deposits into the vault.
+#[event]
+pub struct DepositEvent {
+    /// The vault deposited into
+    pub vault_state: Pubkey,
+    /// The depositing user
+    pub user: Pubkey,
+    /// Deposit tokens paid by the user, including the entry fee
+    pub deposit_amount: u64,
+    /// Entry fee sent to the treasury
+    pub fee_amount: u64,
+    /// IOU tokens minted to the user
+    pub iou_amount: u64,
+    /// Exchange rate the deposit was priced at
+    pub exchange_rate: u128,
+    /// Epoch of the deposit
+    pub epoch: u64,
+}
+
+/// Emitted when a user burns IOU tokens into a withdrawal ticket.
+#[event]
+pub struct WithdrawRequestedEvent {
+    /// The vault withdrawn from
+    pub vault_state: Pubkey,
+    /// The withdrawing user
+    pub user: Pubkey,
+    /// Index of the new ticket within the user's position
+    pub ticket_index: u64,
+    /// IOU tokens burned
+    pub iou_amount: u64,
+    /// Deposit tokens owed if the ticket's value is locked (0 otherwise)
+    pub locked_deposit_amount: u64,
+    /// Exchange rate at request time
+    pub exchange_rate: u128,
+    /// Epoch of the request
+    pub epoch: u64,
+    /// Epoch from which the ticket can be claimed
+    pub unlock_epoch: u64,
+}
+
+/// Emitted when a withdrawal ticket is claimed.
+#[event]
+pub struct WithdrawClaimedEvent {
+    /// The vault withdrawn from
+    pub vault_state: Pubkey,
+    /// The claiming user
+    pub user: Pubkey,
+    /// Index of the claimed ticket
+    pub ticket_index: u64,
+    /// IOU tokens the ticket was opened with
+    pub iou_amount: u64,
+    /// Deposit tokens the ticket redeemed for, including the exit fee
+    pub deposit_amount: u64,
+    /// Exit fee sent to the treasury
+    pub fee_amount: u64,
+    /// Exchange rate at claim time
+    pub exchange_rate: u128,
+    /// Epoch of the claim
+    pub epoch: u64,
+}
+
+/// Emitted when increase_rate raises the exchange rate.
+#[event]
+pub struct RateUpdatedEvent {
+    /// The vault whose exchange rate was raised
+    pub vault_state: Pubkey,
+    /// Exchange rate before the update
+    pub old_exchange_rate: u128,
+    /// Exchange rate after the update
+    pub new_exchange_rate: u128,
+    /// Epoch of the update
+    pub epoch: u64,
+}
+
+/// Emitted when yield is deposited into the vault.
+#[event]
+pub struct YieldDepositedEvent {
+    /// The vault receiving the yield
+    pub vault_state: Pubkey,
+    /// The yield depositor
+    pub yield_depositor: Pubkey,
+    /// Deposit tokens transferred into the vault
+    pub yield_amount: u64,
+    /// Exchange rate after the deposit
+    pub exchange_rate: u128,
+    /// Epoch of the deposit
+    pub epoch: u64,
+}
+
+/// Emitted when the vault advances to a new epoch.
+#[event]
+pub struct EpochAdvancedEvent {
+    /// The vault that advanced
+    pub vault_state: Pubkey,
+    /// The new epoch
+    pub epoch: u64,
+    /// Exchange rate at the start of the new epoch
+    pub exchange_rate: u128,
+    /// Slot at which the new epoch started
+    pub slot: u64,
+    /// Unix timestamp at which the new epoch started
+    pub unix_timestamp: i64,
+}
+
+/// Emitted when apply_loss lowers the exchange rate.
+#[event]
+pub struct LossAppliedEvent {
+    /// The vault whose exchange rate was lowered
+    pub vault_state: Pubkey,
+    /// Exchange rate before the loss
+    pub old_exchange_rate: u128,
+    /// Exchange rate after the loss
+    pub new_exchange_rate: u128,
+    /// Epoch in which the loss was applied
+    pub epoch: u64,
+}
+
+/// Emitted when the rate configuration changes.
+#[event]
+pub struct RateConfigUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// New per-epoch rate increase bound, in basis points
+    pub max_rate_increase_bps: u16,
+    /// New loss authority
+    pub loss_authority: Pubkey,
+}
+
+/// Emitted when the epoch configuration changes.
+#[event]
+pub struct EpochConfigUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// New minimum number of slots per epoch
+    pub min_epoch_slots: u64,
+    /// New minimum number of seconds per epoch
+    pub min_epoch_seconds: i64,
+}
+
+/// Emitted when share-based accounting is switched on or off.
+#[event]
+pub struct ShareBasedAccountingUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// Whether share-based accounting is now enabled
+    pub enabled: bool,
+    /// Exchange rate after re-anchoring
+    pub exchange_rate: u128,
+}
+
+/// Emitted when request-time value locking is switched on or off.
+#[event]
+pub struct LockWithdrawalValueUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// Whether new tickets now lock their value
+    pub enabled: bool,
+}
+
+/// Emitted when the admin proposes a new admin.
+#[event]
+pub struct AdminProposedEvent {
+    /// The vault being handed over
+    pub vault_state: Pubkey,
+    /// Current admin
+    pub admin: Pubkey,
+    /// Proposed admin
+    pub pending_admin: Pubkey,
+}
+
+/// Emitted when a proposed admin accepts the transfer (preceded by a RoleUpdatedEvent for
+/// each role that moved from the old admin).
+#[event]
+pub struct AdminAcceptedEvent {
+    /// The vault handed over
+    pub vault_state: Pubkey,
+    /// Previous admin
+    pub old_admin: Pubkey,
+    /// New admin
+    pub new_admin: Pubkey,
+}
+
+/// Emitted when a role is assigned.
+#[event]
+pub struct RoleUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// The assigned role
+    pub role: VaultRole,
+    /// Key now holding the role
+    pub authority: Pubkey,
+}
+
+/// Emitted when operations are paused or unpaused.
+#[event]
+pub struct PauseFlagsUpdatedEvent {
+    /// The vault whose pause flags changed
+    pub vault_state: Pubkey,
+    /// true for pause, false for unpause
+    pub paused: bool,
+    /// Flags that were set or cleared
+    pub flags: u8,
+    /// Resulting pause bitmask
+    pub paused_flags: u8,
+}
+
+/// Emitted when a performance fee is paid to the treasury.
+#[event]
+pub struct PerformanceFeeAccruedEvent {
+    /// The vault charging the fee
+    pub vault_state: Pubkey,
+    /// IOU tokens minted to the treasury
+    pub fee_shares: u64,
+    /// Exchange rate after the fee
+    pub exchange_rate: u128,
+    /// New high-water mark
+    pub high_water_mark: u128,
+}
+
+/// Emitted when a management fee is paid to the treasury.
+#[event]
+pub struct ManagementFeeAccruedEvent {
+    /// The vault charging the fee
+    pub vault_state: Pubkey,
+    /// IOU tokens minted to the treasury
+    pub fee_shares: u64,
+    /// Exchange rate after the fee
+    pub exchange_rate: u128,
+    /// Unix timestamp the fee was collected up to
+    pub timestamp: i64,
+}
+
+/// Emitted when the management fee changes.
+#[event]
+pub struct ManagementFeeUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// New annual management fee, in basis points
+    pub management_fee_bps: u16,
+}
+
+/// Emitted when the performance fee changes.
+#[event]
+pub struct PerformanceFeeUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// New performance fee, in basis points
+    pub performance_fee_bps: u16,
+    /// Current high-water mark
+    pub high_water_mark: u128,
+}
+
+/// Emitted when the deposit caps change.
+#[event]
+pub struct DepositCapsUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// New vault-wide cap on managed assets (0 = unlimited)
+    pub max_total_assets: u64,
+    /// New per-user cap on outstanding deposits (0 = unlimited)
+    pub max_deposit_per_user: u64,
+}
+
+/// Emitted when a vault state is migrated to the current layout.
+#[event]
+pub struct VaultStateMigratedEvent {
+    /// The migrated vault
+    pub vault_state: Pubkey,
+    /// Layout version before the migration (0 for the unversioned legacy layout)
+    pub from_version: u8,
+    /// Layout version after the migration (VAULT_STATE_VERSION)
+    pub to_version: u8,
+    /// Exchange rate after the migration, scaled by EXCHANGE_RATE_SCALE
+    pub exchange_rate: u128,
+}
+
+/// Emitted when the minimum amounts change.
+#[event]
+pub struct MinimumsUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// New minimum deposit, in deposit tokens
+    pub min_deposit_amount: u64,
+    /// New minimum withdrawal request, in IOU tokens
+    pub min_redeem_iou_amount: u64,
+}
+
+/// Emitted when the fee configuration changes.
+#[event]
+pub struct FeesUpdatedEvent {
+    /// The reconfigured vault
+    pub vault_state: Pubkey,
+    /// New entry fee, in basis points
+    pub entry_fee_bps: u16,
+    /// New exit fee, in basis points
+    pub exit_fee_bps: u16,
+    /// New treasury
+    pub treasury: Pubkey,
+}
+
+/// Emitted when a withdrawal ticket opened by the original program is claimed and closed.
+#[event]
+pub struct LegacyWithdrawClaimedEvent {
+    /// The vault withdrawn from
+    pub vault_state: Pubkey,
+    /// The claiming user
+    pub user: Pubkey,
+    /// IOU tokens the ticket was opened with
+    pub iou_amount: u64,
+    /// Deposit tokens paid out (0 if the original program had already paid the ticket)
+    pub deposit_amount: u64,
+    /// Exchange rate at claim time
+    pub exchange_rate: u128,
+    /// Epoch of the claim
+    pub epoch: u64,
+}
+
+/// Context for the initialize instruction.
+/// Creates the VaultState PDA account and sets initial configuration.
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    /// The admin authority that will control the vault (must sign and pay for account creation)
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The vault state PDA
+    /// Seeds: ["vault_state", deposit_mint]
+    #[account(
+        init,
+        payer = admin,
+        space = VaultState::LEN,
+        seeds = [b"vault_state", deposit_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The deposit token mint (used in PDA seeds)
+    pub deposit_mint: InterfaceAccount<'info, Mint>,
+
+    /// The IOU token mint (stored in VaultState)
+    /// Must be mintable only by the vault, unfreezable and unissued, so every IOU in
+    /// circulation is backed by a deposit
+    #[account(
+        constraint = iou_mint.mint_authority == COption::Some(vault_state.key()) @ VaultError::InvalidIouMint,
+        constraint = iou_mint.freeze_authority.is_none() @ VaultError::InvalidIouMint,
+        constraint = iou_mint.supply == 0 @ VaultError::InvalidIouMint,
+        constraint = iou_mint.decimals == deposit_mint.decimals @ VaultError::InvalidIouMint
+    )]
+    pub iou_mint: InterfaceAccount<'info, Mint>,
+
+    /// The vault's deposit token account: the vault_state's associated token account.
+    /// init_if_needed because anyone can create an associated token account, so a
+    /// pre-created one must not block initialization
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = deposit_mint,
+        associated_token::authority = vault_state,
+        associated_token::token_program = token_program
+    )]
+    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program that owns the deposit mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Associated token program for creating the vault token account
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the deposit and mint_shares instructions.
+/// Transfers deposit tokens from user to vault and mints IOU tokens to user.
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    /// The user making the deposit (must sign)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The vault state PDA
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = deposit_mint @ VaultError::DepositMintMismatch,
+        has_one = iou_mint @ VaultError::IouMintMismatch
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The deposit token mint
+    pub deposit_mint: InterfaceAccount<'info, Mint>,
+
+    /// The IOU token mint
+    #[account(mut)]
+    pub iou_mint: InterfaceAccount<'info, Mint>,
+
+    /// User's deposit token account (source of transfer)
+    #[account(
+        mut,
+        constraint = user_deposit_token_account.mint == deposit_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = user_deposit_token_account.owner == user.key() @ VaultError::TokenAccountOwnerMismatch
+    )]
+    pub user_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Vault's deposit token account (destination of transfer)
+    #[account(
+        mut,
+        address = vault_state.vault_token_account @ VaultError::VaultTokenAccountMismatch
+    )]
+    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// User's IOU token account (destination of mint)
+    #[account(
+        mut,
+        constraint = user_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = user_iou_token_account.owner == user.key() @ VaultError::TokenAccountOwnerMismatch
+    )]
+    pub user_iou_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury's deposit token account (receives the entry fee; may be omitted while
+    /// no entry fee is charged)
+    #[account(
+        mut,
+        constraint = treasury_deposit_token_account.mint == deposit_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_deposit_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_deposit_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury's IOU token account (receives the management fee; may be omitted while no
+    /// management fee is due)
+    #[account(
+        mut,
+        constraint = treasury_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_iou_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User position PDA (tracks the user's deposits against max_deposit_per_user)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [b"user_position", user.key().as_ref(), vault_state.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Token program for transfers and mints
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating the user position
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the request_withdraw and request_withdraw_assets instructions.
+/// Burns IOU tokens and creates a withdrawal ticket.
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    /// The user requesting withdrawal (must sign)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The vault state PDA
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = iou_mint @ VaultError::IouMintMismatch
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The IOU token mint
+    #[account(mut)]
+    pub iou_mint: InterfaceAccount<'info, Mint>,
+
+    /// User's IOU token account (source of burn)
+    #[account(
+        mut,
+        constraint = user_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = user_iou_token_account.owner == user.key() @ VaultError::TokenAccountOwnerMismatch
+    )]
+    pub user_iou_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Vault's deposit token account (its balance prices locked tickets in share-based mode)
+    #[account(address = vault_state.vault_token_account @ VaultError::VaultTokenAccountMismatch)]
+    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury's IOU token account (receives the management fee; may be omitted while no
+    /// management fee is due)
+    #[account(
+        mut,
+        constraint = treasury_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_iou_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User position PDA (one per user per vault, created on first deposit or request)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [b"user_position", user.key().as_ref(), vault_state.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Withdrawal ticket PDA (one per user per vault per ticket index)
+    /// Created fresh on every request and closed on claim, so tickets are never reused
+    /// Space: WithdrawalTicket::LEN (summed field by field next to the struct)
+    #[account(
+        init,
+        payer = user,
+        space = WithdrawalTicket::LEN,
+        seeds = [
+            b"withdrawal_ticket",
+            user.key().as_ref(),
+            vault_state.key().as_ref(),
+            &user_position.next_ticket_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub withdrawal_ticket: Account<'info, WithdrawalTicket>,
+
+    /// Token program for burns and fee mints
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the claim_withdraw instruction.
+/// Transfers deposit tokens from vault to user and closes the withdrawal ticket.
+#[derive(Accounts)]
+pub struct ClaimWithdraw<'info> {
+    /// The user claiming the withdrawal (must sign)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The vault state PDA (mutable to release the ticket's pending IOUs)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = deposit_mint @ VaultError::DepositMintMismatch,
+        has_one = iou_mint @ VaultError::IouMintMismatch
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The deposit token mint
+    pub deposit_mint: InterfaceAccount<'info, Mint>,
+
+    /// The IOU token mint (its supply prices the claim in share-based mode; management
+    /// fees are minted from it)
+    #[account(mut)]
+    pub iou_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault's deposit token account (source of transfer, owned by vault_state PDA)
+    #[account(
+        mut,
+        address = vault_state.vault_token_account @ VaultError::VaultTokenAccountMismatch
+    )]
+    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// User's deposit token account (destination of transfer)
+    #[account(
+        mut,
+        constraint = user_deposit_token_account.mint == deposit_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = user_deposit_token_account.owner == user.key() @ VaultError::TokenAccountOwnerMismatch
+    )]
+    pub user_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury's deposit token account (receives the exit fee; may be omitted while no
+    /// exit fee is charged)
+    #[account(
+        mut,
+        constraint = treasury_deposit_token_account.mint == deposit_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_deposit_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_deposit_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury's IOU token account (receives the management fee; may be omitted while no
+    /// management fee is due)
+    #[account(
+        mut,
+        constraint = treasury_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_iou_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User position PDA (tracks the user's open tickets)
+    #[account(
+        mut,
+        seeds = [b"user_position", user.key().as_ref(), vault_state.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Withdrawal ticket PDA (selects which of the user's tickets to claim)
+    /// Closed after the claim, refunding its rent to the user
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            b"withdrawal_ticket",
+            user.key().as_ref(),
+            vault_state.key().as_ref(),
+            &withdrawal_ticket.ticket_index.to_le_bytes()
+        ],
+        bump = withdrawal_ticket.bump
+    )]
+    pub withdrawal_ticket: Account<'info, WithdrawalTicket>,
+
+    /// Token program for transfers and fee mints
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Context for the increase_rate instruction.
+/// Updates the exchange rate (rate-manager-only).
+#[derive(Accounts)]
+pub struct IncreaseRate<'info> {
+    /// The rate manager (must sign and match vault_state.rate_manager)
+    pub rate_manager: Signer<'info>,
+
+    /// The vault state PDA (mutable to update exchange_rate)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = rate_manager @ VaultError::UnauthorizedRole,
+        has_one = iou_mint @ VaultError::IouMintMismatch
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The IOU token mint (performance fees are minted from it)
+    #[account(mut)]
+    pub iou_mint: InterfaceAccount<'info, Mint>,
+
+    /// Treasury's IOU token account (receives the performance fee)
+    #[account(
+        mut,
+        constraint = treasury_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_iou_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program for fee mints
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Context for the advance_epoch instruction.
+/// Increments the epoch once the minimum epoch duration has passed (epoch-authority-only).
+#[derive(Accounts)]
+pub struct AdvanceEpoch<'info> {
+    /// The epoch authority (must sign and match vault_state.epoch_authority)
+    pub epoch_authority: Signer<'info>,
+
+    /// The vault state PDA (mutable to update current_epoch and its start slot/time)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = epoch_authority @ VaultError::UnauthorizedEpochAuthority
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Context for the apply_loss instruction.
+/// Lowers the exchange rate (rate manager and loss authority must both sign).
+#[derive(Accounts)]
+pub struct ApplyLoss<'info> {
+    /// The rate manager (must sign and match vault_state.rate_manager)
+    pub rate_manager: Signer<'info>,
+
+    /// The loss authority (must sign and match vault_state.loss_authority)
+    pub loss_authority: Signer<'info>,
+
+    /// The vault state PDA (mutable to update exchange_rate)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = rate_manager @ VaultError::UnauthorizedRole,
+        has_one = loss_authority @ VaultError::UnauthorizedLossAuthority
+    )]
+    pub vault_state: Account<'info, VaultState>,
 }
 
-/// Context for the deposit instruction.
-/// Transfers deposit tokens from user to vault and mints IOU tokens to user.
+/// Context for the set_rate_config instruction.
+/// Updates the per-epoch rate increase bound and the loss authority (admin-only).
 #[derive(Accounts)]
-pub struct Deposit<'info> {
-    /// The user making the deposit (must sign)
+pub struct SetRateConfig<'info> {
+    /// The admin authority (must sign and match vault_state.admin)
+    pub admin: Signer<'info>,
+
+    /// The vault state PDA (mutable to update the rate configuration)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = admin @ VaultError::UnauthorizedAdmin
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Context for the set_epoch_config instruction.
+/// Updates the minimum epoch duration (admin-only).
+#[derive(Accounts)]
+pub struct SetEpochConfig<'info> {
+    /// The admin authority (must sign and match vault_state.admin)
+    pub admin: Signer<'info>,
+
+    /// The vault state PDA (mutable to update the epoch configuration)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = admin @ VaultError::UnauthorizedAdmin
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Context for the deposit_yield instruction.
+/// Transfers deposit tokens from the yield depositor to vault without minting IOU tokens
+/// (yield-depositor-only).
+#[derive(Accounts)]
+pub struct DepositYield<'info> {
+    /// The yield depositor (must sign and match vault_state.yield_depositor)
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub yield_depositor: Signer<'info>,
 
-    /// The vault state PDA
+    /// The vault state PDA (mutable to record the derived exchange rate)
     #[account(
         mut,
         seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
-        bump,
-        has_one = deposit_mint @ VaultError::InvalidAmount,
-        has_one = iou_mint @ VaultError::InvalidAmount
+        bump = vault_state.bump,
+        has_one = yield_depositor @ VaultError::UnauthorizedRole,
+        has_one = deposit_mint @ VaultError::DepositMintMismatch,
+        has_one = iou_mint @ VaultError::IouMintMismatch
     )]
     pub vault_state: Account<'info, VaultState>,
 
     /// The deposit token mint
     pub deposit_mint: InterfaceAccount<'info, Mint>,
 
-    /// The IOU token mint
+    /// The IOU token mint (its supply prices the yield in share-based mode; performance
+    /// fees are minted from it)
     #[account(mut)]
     pub iou_mint: InterfaceAccount<'info, Mint>,
 
-    /// User's deposit token account (source of transfer)
+    /// Yield depositor's deposit token account (source of transfer)
     #[account(
         mut,
-        constraint = user_deposit_token_account.mint == deposit_mint.key() @ VaultError::InvalidAmount,
-        constraint = user_deposit_token_account.owner == user.key() @ VaultError::InvalidTicketOwner
+        constraint = yield_depositor_token_account.mint == deposit_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = yield_depositor_token_account.owner == yield_depositor.key() @ VaultError::TokenAccountOwnerMismatch
     )]
-    pub user_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub yield_depositor_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// Vault's deposit token account (destination of transfer)
+    /// Vault's deposit token account (destination of transfer, owned by vault_state PDA)
     #[account(
         mut,
-        constraint = vault_deposit_token_account.mint == deposit_mint.key() @ VaultError::InvalidAmount,
-        constraint = vault_deposit_token_account.owner == vault_state.key() @ VaultError::InvalidTicketOwner
+        address = vault_state.vault_token_account @ VaultError::VaultTokenAccountMismatch
     )]
     pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// User's IOU token account (destination of mint)
+    /// Treasury's IOU token account (receives the performance fee)
     #[account(
         mut,
-        constraint = user_iou_token_account.mint == iou_mint.key() @ VaultError::InvalidAmount,
-        constraint = user_iou_token_account.owner == user.key() @ VaultError::InvalidTicketOwner
+        constraint = treasury_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
     )]
-    pub user_iou_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub treasury_iou_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// Token program for transfers and mints
+    /// Token program for transfers and fee mints
     pub token_program: Interface<'info, TokenInterface>,
 }
 
-/// Context for the request_withdraw instruction.
-/// Burns IOU tokens and creates a withdrawal ticket.
+/// Context for the set_share_based_accounting instruction.
+/// Switches the accounting mode and re-anchors the stored exchange rate (admin-only).
 #[derive(Accounts)]
-pub struct RequestWithdraw<'info> {
-    /// The user requesting withdrawal (must sign)
-    #[account(mut)]
-    pub user: Signer<'info>,
+pub struct SetShareBasedAccounting<'info> {
+    /// The admin authority (must sign and match vault_state.admin)
+    pub admin: Signer<'info>,
 
-    /// The vault state PDA
+    /// The vault state PDA (mutable to update the accounting mode and exchange_rate)
     #[account(
         mut,
         seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
-        bump,
-        has_one = iou_mint @ VaultError::InvalidAmount
+        bump = vault_state.bump,
+        has_one = admin @ VaultError::UnauthorizedAdmin,
+        has_one = iou_mint @ VaultError::IouMintMismatch
     )]
     pub vault_state: Account<'info, VaultState>,
 
-    /// The IOU token mint
-    #[account(mut)]
+    /// The IOU token mint (its supply is the outstanding share count)
     pub iou_mint: InterfaceAccount<'info, Mint>,
 
-    /// User's IOU token account (source of burn)
+    /// Vault's deposit token account (its balance is the vault's managed assets)
+    #[account(address = vault_state.vault_token_account @ VaultError::VaultTokenAccountMismatch)]
+    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Context for the set_lock_withdrawal_value instruction.
+/// Toggles request-time value locking for new withdrawal tickets (admin-only).
+#[derive(Accounts)]
+pub struct SetLockWithdrawalValue<'info> {
+    /// The admin authority (must sign and match vault_state.admin)
+    pub admin: Signer<'info>,
+
+    /// The vault state PDA (mutable to update lock_withdrawal_value)
     #[account(
         mut,
-        constraint = user_iou_token_account.mint == iou_mint.key() @ VaultError::InvalidAmount,
-        constraint = user_iou_token_account.owner == user.key() @ VaultError::InvalidTicketOwner
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = admin @ VaultError::UnauthorizedAdmin
     )]
-    pub user_iou_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Context for the set_deposit_caps instruction.
+/// Updates the vault-wide and per-user deposit caps (admin-only).
+#[derive(Accounts)]
+pub struct SetDepositCaps<'info> {
+    /// The admin authority (must sign and match vault_state.admin)
+    pub admin: Signer<'info>,
 
-    /// Withdrawal ticket PDA (one per user per vault)
-    /// Space: 8 (discriminator) + 32 (user) + 8 (iou_amount) + 8 (unlock_epoch) + 1 (claimed) = 57
+    /// The vault state PDA (mutable to update the deposit caps)
     #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + 32 + 8 + 8 + 1,
-        seeds = [b"withdrawal_ticket", user.key().as_ref(), vault_state.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = admin @ VaultError::UnauthorizedAdmin
     )]
-    pub withdrawal_ticket: Account<'info, WithdrawalTicket>,
+    pub vault_state: Account<'info, VaultState>,
+}
 
-    /// Token program for burns
-    pub token_program: Interface<'info, TokenInterface>,
+/// Context for the set_minimums instruction.
+/// Updates the minimum deposit and redemption amounts (admin-only).
+#[derive(Accounts)]
+pub struct SetMinimums<'info> {
+    /// The admin authority (must sign and match vault_state.admin)
+    pub admin: Signer<'info>,
 
-    /// System program for account creation
-    pub system_program: Program<'info, System>,
+    /// The vault state PDA (mutable to update the minimums)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = admin @ VaultError::UnauthorizedAdmin
+    )]
+    pub vault_state: Account<'info, VaultState>,
 }
 
-/// Context for the claim_withdraw instruction.
-/// Transfers deposit tokens from vault to user and marks withdrawal ticket as claimed.
+/// Context for the propose_admin instruction.
+/// Records a pending admin transfer (admin-only).
 #[derive(Accounts)]
-pub struct ClaimWithdraw<'info> {
-    /// The user claiming the withdrawal (must sign)
-    #[account(mut)]
-    pub user: Signer<'info>,
+pub struct ProposeAdmin<'info> {
+    /// The admin authority (must sign and match vault_state.admin)
+    pub admin: Signer<'info>,
 
-    /// The vault state PDA
+    /// The vault state PDA (mutable to update pending_admin)
     #[account(
+        mut,
         seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
-        bump,
-        has_one = deposit_mint @ VaultError::InvalidAmount
+        bump = vault_state.bump,
+        has_one = admin @ VaultError::UnauthorizedAdmin
     )]
     pub vault_state: Account<'info, VaultState>,
+}
 
-    /// The deposit token mint
-    pub deposit_mint: InterfaceAccount<'info, Mint>,
+/// Context for the accept_admin instruction.
+/// Completes a pending admin transfer (pending-admin-only).
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// The proposed admin (must sign and match vault_state.pending_admin)
+    pub pending_admin: Signer<'info>,
 
-    /// Vault's deposit token account (source of transfer, owned by vault_state PDA)
+    /// The vault state PDA (mutable to update admin, pending_admin and the roles moving
+    /// with the admin)
     #[account(
         mut,
-        constraint = vault_deposit_token_account.mint == deposit_mint.key() @ VaultError::InvalidAmount,
-        constraint = vault_deposit_token_account.owner == vault_state.key() @ VaultError::InvalidTicketOwner
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = pending_admin @ VaultError::UnauthorizedPendingAdmin
     )]
-    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub vault_state: Account<'info, VaultState>,
+}
 
-    /// User's deposit token account (destination of transfer)
+/// Context for the set_role instruction.
+/// Assigns an operational role (admin-only).
+#[derive(Accounts)]
+pub struct SetRole<'info> {
+    /// The admin authority (must sign and match vault_state.admin)
+    pub admin: Signer<'info>,
+
+    /// The vault state PDA (mutable to update the role)
     #[account(
         mut,
-        constraint = user_deposit_token_account.mint == deposit_mint.key() @ VaultError::InvalidAmount,
-        constraint = user_deposit_token_account.owner == user.key() @ VaultError::InvalidTicketOwner
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = admin @ VaultError::UnauthorizedAdmin
     )]
-    pub user_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Context for the pause and unpause instructions.
+/// Sets or clears pause flags (pauser-only).
+#[derive(Accounts)]
+pub struct UpdatePauseFlags<'info> {
+    /// The pauser (must sign and match vault_state.pauser)
+    pub pauser: Signer<'info>,
 
-    /// Withdrawal ticket PDA
+    /// The vault state PDA (mutable to update paused_flags)
     #[account(
         mut,
-        seeds = [b"withdrawal_ticket", user.key().as_ref(), vault_state.key().as_ref()],
-        bump
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = pauser @ VaultError::UnauthorizedRole
     )]
-    pub withdrawal_ticket: Account<'info, WithdrawalTicket>,
+    pub vault_state: Account<'info, VaultState>,
+}
 
-    /// Token program for transfers
-    pub token_program: Interface<'info, TokenInterface>,
+/// Context for the set_fees instruction.
+/// Updates the fee rates and the treasury (fee-manager-only).
+#[derive(Accounts)]
+#[instruction(entry_fee_bps: u16, exit_fee_bps: u16, treasury: Pubkey)]
+pub struct SetFees<'info> {
+    /// The fee manager (must sign and match vault_state.fee_manager)
+    pub fee_manager: Signer<'info>,
+
+    /// The vault state PDA (mutable to update the fee configuration)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = fee_manager @ VaultError::UnauthorizedRole
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// New treasury's deposit token account (will receive entry and exit fees)
+    #[account(
+        constraint = treasury_deposit_token_account.mint == vault_state.deposit_mint @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_deposit_token_account.owner == treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// New treasury's IOU token account (will receive performance and management fees)
+    #[account(
+        constraint = treasury_iou_token_account.mint == vault_state.iou_mint @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_iou_token_account: InterfaceAccount<'info, TokenAccount>,
 }
 
-/// Context for the increase_rate instruction.
-/// Updates exchange rate and increments epoch (admin-only).
+/// Context for the set_performance_fee instruction.
+/// Updates the performance fee rate (fee-manager-only).
 #[derive(Accounts)]
-pub struct IncreaseRate<'info> {
-    /// The admin authority (must sign and match vault_state.admin)
-    pub admin: Signer<'info>,
+pub struct SetPerformanceFee<'info> {
+    /// The fee manager (must sign and match vault_state.fee_manager)
+    pub fee_manager: Signer<'info>,
 
-    /// The vault state PDA (mutable to update exchange_rate and current_epoch)
+    /// The vault state PDA (mutable to update the fee configuration)
     #[account(
         mut,
         seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
-        bump,
-        has_one = admin @ VaultError::UnauthorizedAdmin
+        bump = vault_state.bump,
+        has_one = fee_manager @ VaultError::UnauthorizedRole
     )]
     pub vault_state: Account<'info, VaultState>,
 }
 
-/// Context for the deposit_yield instruction.
-/// Transfers deposit tokens from admin to vault without minting IOU tokens (admin-only).
+/// Context for the set_management_fee instruction.
+/// Collects the fee accrued so far and updates the management fee (fee-manager-only).
 #[derive(Accounts)]
-pub struct DepositYield<'info> {
-    /// The admin authority (must sign and match vault_state.admin)
+pub struct SetManagementFee<'info> {
+    /// The fee manager (must sign and match vault_state.fee_manager)
+    pub fee_manager: Signer<'info>,
+
+    /// The vault state PDA (mutable to update the fee configuration)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = fee_manager @ VaultError::UnauthorizedRole,
+        has_one = iou_mint @ VaultError::IouMintMismatch
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The IOU token mint (management fees are minted from it)
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub iou_mint: InterfaceAccount<'info, Mint>,
 
-    /// The vault state PDA
+    /// Treasury's IOU token account (receives the management fee)
+    #[account(
+        mut,
+        constraint = treasury_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_iou_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program for fee mints
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Context for the crank_fees instruction.
+/// Collects the accrued management fee (permissionless).
+#[derive(Accounts)]
+pub struct CrankFees<'info> {
+    /// The vault state PDA (mutable to update the exchange rate and accrual timestamp)
     #[account(
+        mut,
         seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = iou_mint @ VaultError::IouMintMismatch
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The IOU token mint (management fees are minted from it)
+    #[account(mut)]
+    pub iou_mint: InterfaceAccount<'info, Mint>,
+
+    /// Treasury's IOU token account (receives the management fee)
+    #[account(
+        mut,
+        constraint = treasury_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_iou_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program for fee mints
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Context for the migrate_vault_state instruction.
+/// Rewrites a vault state from an older layout to the current one (admin-only).
+#[derive(Accounts)]
+pub struct MigrateVaultState<'info> {
+    /// The admin authority (must sign, match the stored vault_state.admin and pay any
+    /// extra rent)
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The deposit token mint the vault was created for
+    pub deposit_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: The vault state PDA in an older layout, which Account<VaultState> may not
+    /// deserialize. Validated by its seeds and owner here, and against its layout,
+    /// version and admin in the handler.
+    #[account(
+        mut,
+        seeds = [b"vault_state", deposit_mint.key().as_ref()],
         bump,
-        has_one = admin @ VaultError::UnauthorizedAdmin
+        owner = crate::ID
+    )]
+    pub vault_state: UncheckedAccount<'info>,
+
+    /// The vault's existing deposit token account, recorded in the migrated state
+    #[account(
+        constraint = vault_deposit_token_account.mint == deposit_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = vault_deposit_token_account.owner == vault_state.key() @ VaultError::VaultTokenAccountMismatch
+    )]
+    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// System program (funds the extra rent)
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the claim_legacy_withdraw instruction.
+/// Pays out and closes a withdrawal ticket opened by the original program.
+#[derive(Accounts)]
+pub struct ClaimLegacyWithdraw<'info> {
+    /// The user claiming the withdrawal (must sign, receives the ticket's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The vault state PDA, in the current layout (mutable to release the ticket's
+    /// pending IOUs)
+    #[account(
+        mut,
+        seeds = [b"vault_state", vault_state.deposit_mint.as_ref()],
+        bump = vault_state.bump,
+        has_one = deposit_mint @ VaultError::DepositMintMismatch,
+        has_one = iou_mint @ VaultError::IouMintMismatch
     )]
     pub vault_state: Account<'info, VaultState>,
 
     /// The deposit token mint
     pub deposit_mint: InterfaceAccount<'info, Mint>,
 
-    /// Admin's deposit token account (source of transfer)
+    /// The IOU token mint (its supply prices the claim in share-based mode; management
+    /// fees are minted from it)
+    #[account(mut)]
+    pub iou_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault's deposit token account (source of transfer, owned by vault_state PDA)
     #[account(
         mut,
-        constraint = admin_deposit_token_account.mint == deposit_mint.key() @ VaultError::InvalidAmount,
-        constraint = admin_deposit_token_account.owner == admin.key() @ VaultError::InvalidTicketOwner
+        address = vault_state.vault_token_account @ VaultError::VaultTokenAccountMismatch
     )]
-    pub admin_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// Vault's deposit token account (destination of transfer, owned by vault_state PDA)
+    /// User's deposit token account (destination of transfer)
     #[account(
         mut,
-        constraint = vault_deposit_token_account.mint == deposit_mint.key() @ VaultError::InvalidAmount,
-        constraint = vault_deposit_token_account.owner == vault_state.key() @ VaultError::InvalidTicketOwner
+        constraint = user_deposit_token_account.mint == deposit_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = user_deposit_token_account.owner == user.key() @ VaultError::TokenAccountOwnerMismatch
     )]
-    pub vault_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_deposit_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury's IOU token account (receives the management fee; may be omitted while no
+    /// management fee is due)
+    #[account(
+        mut,
+        constraint = treasury_iou_token_account.mint == iou_mint.key() @ VaultError::TokenAccountMintMismatch,
+        constraint = treasury_iou_token_account.owner == vault_state.treasury @ VaultError::TreasuryOwnerMismatch
+    )]
+    pub treasury_iou_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The user's withdrawal ticket in the original layout, which
+    /// Account<WithdrawalTicket> cannot deserialize. Validated by its seeds (no ticket
+    /// index) and owner here, and against its layout and user in the handler.
+    #[account(
+        mut,
+        seeds = [b"withdrawal_ticket", user.key().as_ref(), vault_state.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub withdrawal_ticket: UncheckedAccount<'info>,
 
-    /// Token program for transfers
+    /// Token program for transfers and fee mints
     pub token_program: Interface<'info, TokenInterface>,
 }
 
@@ -634,4 +3644,207 @@ pub enum VaultError {
     UnauthorizedAdmin,
     #[msg("Insufficient vault balance - vault does not have enough tokens to fulfill withdrawal")]
     InsufficientVaultBalance,
+    #[msg("Manual rate changes are disabled - the exchange rate is derived from vault assets")]
+    ManualRateDisabled,
+    #[msg("Unauthorized - only the epoch authority can advance the epoch")]
+    UnauthorizedEpochAuthority,
+    #[msg("Epoch advanced too early - minimum epoch duration not reached")]
+    EpochTooEarly,
+    #[msg("Invalid epoch configuration")]
+    InvalidEpochConfig,
+    #[msg("Exchange rate decreases must go through apply_loss")]
+    RateDecreaseNotAllowed,
+    #[msg("Exchange rate increase exceeds the per-epoch maximum")]
+    RateIncreaseTooLarge,
+    #[msg("Unauthorized - only the loss authority can co-sign a loss")]
+    UnauthorizedLossAuthority,
+    #[msg("Invalid rate configuration")]
+    InvalidRateConfig,
+    #[msg("Unauthorized - signer does not hold the required vault role")]
+    UnauthorizedRole,
+    #[msg("Unauthorized - only the pending admin can accept the admin transfer")]
+    UnauthorizedPendingAdmin,
+    #[msg("Operation is paused")]
+    Paused,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    #[msg("Invalid fee configuration")]
+    InvalidFeeConfig,
+    #[msg("Deposit would exceed the vault's total asset cap")]
+    DepositCapExceeded,
+    #[msg("Deposit would exceed the per-user deposit cap")]
+    UserDepositCapExceeded,
+    #[msg("Deposit is below the vault's minimum deposit amount")]
+    DepositBelowMinimum,
+    #[msg("Withdrawal request is below the vault's minimum redemption amount")]
+    RedeemBelowMinimum,
+    #[msg("Output is below the caller's minimum amount")]
+    SlippageExceeded,
+    #[msg("Vault state already uses the current layout")]
+    AlreadyMigrated,
+    #[msg("IOU mint must be controlled by the vault, unfreezable, unissued and match the deposit mint's decimals")]
+    InvalidIouMint,
+    #[msg("Token account is not the vault's deposit token account")]
+    VaultTokenAccountMismatch,
+    #[msg("Deposit mint does not match the vault's deposit mint")]
+    DepositMintMismatch,
+    #[msg("IOU mint does not match the vault's IOU mint")]
+    IouMintMismatch,
+    #[msg("Token account holds a different mint than the instruction expects")]
+    TokenAccountMintMismatch,
+    #[msg("Token account is not owned by the signer")]
+    TokenAccountOwnerMismatch,
+    #[msg("Treasury token account is not owned by the vault's treasury")]
+    TreasuryOwnerMismatch,
+    #[msg("Treasury token account is required to receive a fee")]
+    TreasuryAccountMissing,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_vault_state_migrates_with_unchanged_prices_and_defaults() {
+        let legacy = LegacyVaultState {
+            admin: Pubkey::new_unique(),
+            deposit_mint: Pubkey::new_unique(),
+            iou_mint: Pubkey::new_unique(),
+            exchange_rate: 1_100_000,
+            current_epoch: 7,
+        };
+        let admin = legacy.admin;
+        let vault_token_account = Pubkey::new_unique();
+        let clock = Clock {
+            slot: 42,
+            unix_timestamp: 1_700_000_000,
+            ..Clock::default()
+        };
+
+        let mut data = VaultState::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), LegacyVaultState::LEN);
+        assert_eq!(data.len(), 120);
+
+        let migrated = LegacyVaultState::deserialize(&mut &data[8..])
+            .unwrap()
+            .into_current(vault_token_account, 254, 500, &clock);
+        assert_eq!(migrated.admin, admin);
+        assert_eq!(migrated.current_epoch, 7);
+        assert_eq!(migrated.vault_token_account, vault_token_account);
+        assert_eq!(migrated.bump, 254);
+        assert_eq!(migrated.version, VAULT_STATE_VERSION);
+        assert_eq!(migrated.reserved, [0; 128]);
+
+        // Prices are unchanged and fees only apply to gains from here on
+        let rate = EXCHANGE_RATE_SCALE * 11 / 10;
+        assert_eq!(migrated.exchange_rate, rate);
+        assert_eq!(migrated.epoch_start_rate, rate);
+        assert_eq!(migrated.high_water_mark, rate);
+        assert_eq!(
+            migrated.max_rate_increase_bps,
+            DEFAULT_MAX_RATE_INCREASE_BPS
+        );
+
+        // Unpaid legacy tickets stay outstanding until claim_legacy_withdraw
+        assert_eq!(migrated.pending_iou_amount, 500);
+
+        // The admin holds every role, as after initialize
+        for role in [
+            migrated.epoch_authority,
+            migrated.rate_manager,
+            migrated.yield_depositor,
+            migrated.pauser,
+            migrated.fee_manager,
+            migrated.treasury,
+        ] {
+            assert_eq!(role, admin);
+        }
+        assert_eq!(migrated.loss_authority, Pubkey::default());
+        assert_eq!(migrated.pending_admin, Pubkey::default());
+
+        // Epoch and fee clocks start at the migration, with the default epoch duration
+        assert_eq!(migrated.min_epoch_slots, DEFAULT_MIN_EPOCH_SLOTS);
+        assert_eq!(migrated.min_epoch_seconds, DEFAULT_MIN_EPOCH_SECONDS);
+        assert_eq!(migrated.last_epoch_slot, 42);
+        assert_eq!(migrated.last_epoch_timestamp, 1_700_000_000);
+        assert_eq!(migrated.last_fee_accrual_ts, 1_700_000_000);
+
+        let mut data = Vec::new();
+        migrated.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), VaultState::LEN);
+    }
+
+    #[test]
+    fn transfer_admin_moves_roles_still_held_by_the_old_admin() {
+        let old_admin = Pubkey::new_unique();
+        let new_admin = Pubkey::new_unique();
+        let pauser = Pubkey::new_unique();
+        let mut vault_state = LegacyVaultState {
+            admin: old_admin,
+            deposit_mint: Pubkey::new_unique(),
+            iou_mint: Pubkey::new_unique(),
+            exchange_rate: 1_000_000,
+            current_epoch: 0,
+        }
+        .into_current(Pubkey::new_unique(), 255, 0, &Clock::default());
+        vault_state.pauser = pauser;
+        vault_state.loss_authority = old_admin;
+        vault_state.pending_admin = new_admin;
+
+        let moved_roles = vault_state.transfer_admin(new_admin);
+        assert_eq!(
+            moved_roles,
+            [
+                VaultRole::RateManager,
+                VaultRole::YieldDepositor,
+                VaultRole::FeeManager,
+                VaultRole::EpochAuthority,
+            ]
+        );
+        assert_eq!(vault_state.admin, new_admin);
+        assert_eq!(vault_state.pending_admin, Pubkey::default());
+        for role in moved_roles {
+            assert_eq!(*vault_state.role_mut(role), new_admin);
+        }
+        assert_eq!(vault_state.treasury, new_admin);
+        assert_eq!(vault_state.loss_authority, new_admin);
+
+        // A role delegated away from the admin stays with its holder
+        assert_eq!(vault_state.pauser, pauser);
+    }
+
+    #[test]
+    fn management_fee_accrues_pro_rata_and_keeps_vault_value() {
+        let mut vault_state = LegacyVaultState {
+            admin: Pubkey::new_unique(),
+            deposit_mint: Pubkey::new_unique(),
+            iou_mint: Pubkey::new_unique(),
+            exchange_rate: 1_000_000,
+            current_epoch: 0,
+        }
+        .into_current(Pubkey::new_unique(), 255, 0, &Clock::default());
+        vault_state.management_fee_bps = 100;
+
+        // 1% per year over half a year of 1_000_000_000 shares
+        let now = (SECONDS_PER_YEAR / 2) as i64;
+        let fee_shares = vault_state
+            .accrue_management_fee(1_000_000_000, now)
+            .unwrap();
+        assert_eq!(fee_shares, 5_000_000);
+        assert_eq!(vault_state.last_fee_accrual_ts, now);
+        assert_eq!(
+            vault_state.exchange_rate,
+            EXCHANGE_RATE_SCALE * 1_000_000_000 / 1_005_000_000
+        );
+
+        // A second later the fee rounds to zero and the accrual clock stays put
+        assert_eq!(
+            vault_state
+                .accrue_management_fee(1_005_000_000, now + 1)
+                .unwrap(),
+            0
+        );
+        assert_eq!(vault_state.last_fee_accrual_ts, now);
+    }
 }
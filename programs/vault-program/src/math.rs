@@ -0,0 +1,329 @@
+//! Fixed-point helpers with explicit rounding.
+//!
+//! Rounding policy: every conversion rounds in favor of the vault, so the holders who
+//! remain in the vault never pay for another user's rounding.
+//! - Assets in -> IOUs out (deposit): floor, the user receives at most what they paid for
+//! - IOUs in -> assets out (claim, locked tickets): floor, the user is paid at most what
+//!   they burned
+//! - IOUs out -> assets in (mint_shares): ceil, the user pays at least what they receive
+//! - Assets out -> IOUs in (request_withdraw_assets): ceil, the user burns at least what
+//!   they redeem
+//! - Fees charged to users (entry, exit, performance, management): floor, the fee never
+//!   exceeds its nominal rate
+//! - Exchange rates derived from vault holdings (share-based mode): up when pricing assets
+//!   in (deposit, mint_shares), down when pricing assets out (claims, locked tickets,
+//!   request_withdraw_assets), so the rate itself never rounds in the user's favor
+
+use anchor_lang::prelude::*;
+
+use crate::{VaultError, EXCHANGE_RATE_SCALE, VIRTUAL_ASSETS, VIRTUAL_SHARES};
+
+/// Computes `a * b / denominator` rounded down. The product is taken in u128, so it
+/// cannot overflow; only a result that does not fit in u64 (or a zero denominator) fails
+/// with MathOverflow.
+pub fn mul_div_floor(a: u64, b: u64, denominator: u64) -> Result<u64> {
+    to_u64(mul_div_floor_u128(
+        u128::from(a),
+        u128::from(b),
+        u128::from(denominator),
+    )?)
+}
+
+/// Computes `a * b / denominator` rounded up. Same overflow behavior as mul_div_floor.
+pub fn mul_div_ceil(a: u64, b: u64, denominator: u64) -> Result<u64> {
+    to_u64(mul_div_ceil_u128(
+        u128::from(a),
+        u128::from(b),
+        u128::from(denominator),
+    )?)
+}
+
+/// Computes `a * b / denominator` rounded down, for exchange-rate math. Fails with
+/// MathOverflow if the product does not fit in u128 or the denominator is zero.
+pub fn mul_div_floor_u128(a: u128, b: u128, denominator: u128) -> Result<u128> {
+    require!(denominator > 0, VaultError::MathOverflow);
+    let product = a.checked_mul(b).ok_or(VaultError::MathOverflow)?;
+    Ok(product / denominator)
+}
+
+/// Computes `a * b / denominator` rounded up. Same overflow behavior as
+/// mul_div_floor_u128.
+pub fn mul_div_ceil_u128(a: u128, b: u128, denominator: u128) -> Result<u128> {
+    require!(denominator > 0, VaultError::MathOverflow);
+    let product = a.checked_mul(b).ok_or(VaultError::MathOverflow)?;
+    Ok(product.div_ceil(denominator))
+}
+
+/// IOU tokens worth `assets` deposit tokens at `exchange_rate`, rounded down.
+pub fn assets_to_ious_floor(assets: u64, exchange_rate: u128) -> Result<u64> {
+    to_u64(mul_div_floor_u128(
+        u128::from(assets),
+        EXCHANGE_RATE_SCALE,
+        exchange_rate,
+    )?)
+}
+
+/// IOU tokens worth `assets` deposit tokens at `exchange_rate`, rounded up.
+pub fn assets_to_ious_ceil(assets: u64, exchange_rate: u128) -> Result<u64> {
+    to_u64(mul_div_ceil_u128(
+        u128::from(assets),
+        EXCHANGE_RATE_SCALE,
+        exchange_rate,
+    )?)
+}
+
+/// Deposit tokens worth `ious` IOU tokens at `exchange_rate`, rounded down.
+pub fn ious_to_assets_floor(ious: u64, exchange_rate: u128) -> Result<u64> {
+    to_u64(mul_div_floor_u128(
+        u128::from(ious),
+        exchange_rate,
+        EXCHANGE_RATE_SCALE,
+    )?)
+}
+
+/// Deposit tokens worth `ious` IOU tokens at `exchange_rate`, rounded up.
+pub fn ious_to_assets_ceil(ious: u64, exchange_rate: u128) -> Result<u64> {
+    to_u64(mul_div_ceil_u128(
+        u128::from(ious),
+        exchange_rate,
+        EXCHANGE_RATE_SCALE,
+    )?)
+}
+
+/// Direction in which a derived exchange rate is rounded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Toward zero; used when the user is paid out at the rate
+    Down,
+    /// Away from zero; used when the user pays in at the rate
+    Up,
+}
+
+/// Exchange rate of `shares` outstanding IOU tokens backed by `assets` deposit tokens,
+/// rounded in the given direction. VIRTUAL_ASSETS and VIRTUAL_SHARES are added to both
+/// sides, so a fresh vault still prices at 1:1 but a donation cannot inflate the price of
+/// a near-empty vault for free: the virtual share keeps its cut of every donated asset.
+pub fn share_rate(assets: u64, shares: u64, rounding: Rounding) -> Result<u128> {
+    let assets = u128::from(assets) + u128::from(VIRTUAL_ASSETS);
+    let shares = u128::from(shares) + u128::from(VIRTUAL_SHARES);
+    match rounding {
+        Rounding::Down => mul_div_floor_u128(assets, EXCHANGE_RATE_SCALE, shares),
+        Rounding::Up => mul_div_ceil_u128(assets, EXCHANGE_RATE_SCALE, shares),
+    }
+}
+
+/// Narrows a u128 result to u64, failing with MathOverflow if it does not fit.
+pub fn to_u64(value: u128) -> Result<u64> {
+    Ok(u64::try_from(value).map_err(|_| VaultError::MathOverflow)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small deterministic xorshift generator, so failures are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Uniform-ish value in 1..=max.
+        fn below(&mut self, max: u64) -> u64 {
+            self.next() % max + 1
+        }
+
+        /// Exchange rate between 1e-18 and about 74 deposit tokens per IOU.
+        fn rate(&mut self) -> u128 {
+            u128::from(self.below(u64::MAX)) * 4
+        }
+    }
+
+    const CASES: usize = 10_000;
+
+    #[test]
+    fn floor_and_ceil_bracket_the_exact_quotient() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..CASES {
+            let a = rng.below(u64::MAX >> 24);
+            let b = rng.below(1 << 24);
+            let d = rng.below(1 << 40);
+            let exact = u128::from(a) * u128::from(b);
+
+            let floor = mul_div_floor(a, b, d).unwrap();
+            let ceil = mul_div_ceil(a, b, d).unwrap();
+            assert!(u128::from(floor) * u128::from(d) <= exact);
+            assert!(u128::from(ceil) * u128::from(d) >= exact);
+            assert!(ceil - floor <= 1);
+            assert_eq!(ceil == floor, exact % u128::from(d) == 0);
+        }
+    }
+
+    #[test]
+    fn rejects_zero_denominator_and_overflow() {
+        assert!(mul_div_floor(1, 1, 0).is_err());
+        assert!(mul_div_ceil(1, 1, 0).is_err());
+        assert!(mul_div_floor(u64::MAX, 2, 1).is_err());
+        assert!(mul_div_ceil(u64::MAX, 2, 1).is_err());
+        assert_eq!(
+            mul_div_floor(u64::MAX, u64::MAX, u64::MAX).unwrap(),
+            u64::MAX
+        );
+        assert!(mul_div_floor_u128(u128::MAX, 2, u128::MAX).is_err());
+        assert!(ious_to_assets_floor(u64::MAX, u128::MAX).is_err());
+    }
+
+    #[test]
+    fn scales_amounts_beyond_the_u64_product_ceiling() {
+        // 100 million tokens of a 9-decimal mint at a 2.5x rate
+        let amount = 100_000_000 * 1_000_000_000;
+        let rate = EXCHANGE_RATE_SCALE * 5 / 2;
+
+        let ious = assets_to_ious_floor(amount, rate).unwrap();
+        assert_eq!(ious, 40_000_000 * 1_000_000_000);
+        assert_eq!(ious_to_assets_floor(ious, rate).unwrap(), amount);
+    }
+
+    #[test]
+    fn represents_sub_ppm_rate_changes() {
+        // 0.0001% daily yield on a 1:1 rate, which a 1e6-scaled rate rounds away
+        let rate = EXCHANGE_RATE_SCALE + EXCHANGE_RATE_SCALE / 1_000_000;
+        let ious = 1_000_000_000_000_000;
+        assert_eq!(
+            ious_to_assets_floor(ious, rate).unwrap(),
+            1_000_001_000_000_000
+        );
+    }
+
+    #[test]
+    fn deposit_then_claim_never_returns_more_than_deposited() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..CASES {
+            let assets = rng.below(1 << 50);
+            let rate = rng.rate();
+
+            let ious = assets_to_ious_floor(assets, rate).unwrap();
+            let redeemed = ious_to_assets_floor(ious, rate).unwrap();
+            assert!(redeemed <= assets);
+        }
+    }
+
+    #[test]
+    fn exact_amount_entry_points_never_undercharge() {
+        let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+        for _ in 0..CASES {
+            let amount = rng.below(1 << 50);
+            let rate = rng.rate();
+
+            // mint_shares: the assets paid must buy at least the IOUs minted
+            let cost = ious_to_assets_ceil(amount, rate).unwrap();
+            assert!(assets_to_ious_floor(cost, rate).unwrap() >= amount);
+
+            // request_withdraw_assets: the IOUs burned must be worth the assets redeemed
+            if let Ok(burn) = assets_to_ious_ceil(amount, rate) {
+                assert!(ious_to_assets_floor(burn, rate).unwrap() >= amount);
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_cycles_do_not_leak_value_in_share_based_mode() {
+        let mut rng = Rng(0x94d0_49bb_1331_11eb);
+        let rate_in = |assets: u64, shares: u64| share_rate(assets, shares, Rounding::Up).unwrap();
+        let rate_out =
+            |assets: u64, shares: u64| share_rate(assets, shares, Rounding::Down).unwrap();
+        for vault in 0..200 {
+            // An existing vault whose share price does not divide evenly; every other
+            // vault has taken a deep loss, so its rate is small enough for the rounding
+            // of the rate itself to be worth whole IOUs
+            let (mut assets, mut shares, start_balance) = if vault % 2 == 0 {
+                let assets = rng.below(1 << 40);
+                (assets, rng.below(assets), rng.below(1 << 40))
+            } else {
+                (
+                    (1 << 20) + rng.below(1 << 20),
+                    rng.below(1 << 50),
+                    rng.below(1 << 30),
+                )
+            };
+            let mut balance = start_balance;
+
+            for cycle in 0..50 {
+                // Buy IOUs with part of the balance at the asset-backed rate, alternating
+                // between deposit (exact assets) and mint_shares (exact IOUs)...
+                let rate = rate_in(assets, shares);
+                let (paid, ious) = if cycle % 2 == 0 {
+                    let amount = rng.below(balance);
+                    (amount, assets_to_ious_floor(amount, rate).unwrap())
+                } else {
+                    let ious = rng.below(assets_to_ious_floor(balance, rate).unwrap().max(1));
+                    (ious_to_assets_ceil(ious, rate).unwrap(), ious)
+                };
+                if paid > balance {
+                    continue;
+                }
+
+                let (assets_before, shares_before) = (assets, shares);
+                balance -= paid;
+                assets += paid;
+                shares += ious;
+
+                // The purchase must not dilute the holders already in the vault
+                assert!(
+                    u128::from(assets + VIRTUAL_ASSETS)
+                        * u128::from(shares_before + VIRTUAL_SHARES)
+                        >= u128::from(assets_before + VIRTUAL_ASSETS)
+                            * u128::from(shares + VIRTUAL_SHARES)
+                );
+
+                // ...then redeem everything at the rate the purchase produced
+                let redeemed = ious_to_assets_floor(ious, rate_out(assets, shares)).unwrap();
+                balance += redeemed;
+                assets -= redeemed;
+                shares -= ious;
+
+                if balance == 0 {
+                    break;
+                }
+            }
+
+            assert!(balance <= start_balance);
+        }
+    }
+
+    #[test]
+    fn inflating_the_share_price_never_pays_off() {
+        let mut rng = Rng(0xbf58_476d_1ce4_e5b9);
+        for _ in 0..CASES {
+            // The attacker opens an empty vault at the stored 1:1 rate, then donates
+            let attacker_shares = rng.below(1 << 20);
+            let donation = rng.below(1 << 40);
+            let attacker_paid = attacker_shares + donation;
+            let mut assets = attacker_paid;
+            let mut shares = attacker_shares;
+
+            // The victim's deposit is rounded down (a zero-IOU deposit reverts)
+            let victim_assets = rng.below(1 << 42);
+            let victim_shares = assets_to_ious_floor(
+                victim_assets,
+                share_rate(assets, shares, Rounding::Up).unwrap(),
+            )
+            .unwrap();
+            if victim_shares > 0 {
+                assets += victim_assets;
+                shares += victim_shares;
+            }
+
+            let attacker_out = ious_to_assets_floor(
+                attacker_shares,
+                share_rate(assets, shares, Rounding::Down).unwrap(),
+            )
+            .unwrap();
+            assert!(attacker_out <= attacker_paid);
+        }
+    }
+}
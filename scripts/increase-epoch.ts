@@ -10,8 +10,21 @@ const PROGRAM_ID = new PublicKey(
   "D7KrGPhkyWsqMRS7kQjaGzyT48nTaw4AopWM6qXXmBtg"
 );
 
-// Exchange rate scale factor (matches on-chain constant)
-const EXCHANGE_RATE_SCALE = 1_000_000;
+// Exchange rate scale factor (matches on-chain constant, 1e18)
+const EXCHANGE_RATE_DECIMALS = 18;
+const EXCHANGE_RATE_SCALE = new anchor.BN(10).pow(
+  new anchor.BN(EXCHANGE_RATE_DECIMALS)
+);
+
+// Format a scaled exchange rate for display (9 of its 18 decimals)
+function formatRate(rate: anchor.BN): string {
+  const fraction = rate
+    .mod(EXCHANGE_RATE_SCALE)
+    .toString()
+    .padStart(EXCHANGE_RATE_DECIMALS, "0")
+    .slice(0, 9);
+  return `${rate.div(EXCHANGE_RATE_SCALE).toString()}.${fraction}`;
+}
 
 // Set environment variables for Anchor to use
 process.env.ANCHOR_PROVIDER_URL = clusterApiUrl("devnet");
@@ -44,7 +57,7 @@ async function increaseEpoch() {
     console.error("\nExample:");
     console.error("  npx ts-node scripts/increase-epoch.ts 3mJFZXLudQF1YgyoWJ5gB6Q97kRoVyE1C6UCihNc9xVN");
     console.error("\nNote: This will increment the epoch by 1 without changing the exchange rate.");
-    console.error("      It does this by calling increase_rate with the current exchange rate.");
+    console.error("      It calls advance_epoch, so the wallet must be the vault's epoch authority.");
     process.exit(1);
   }
 
@@ -61,7 +74,7 @@ async function increaseEpoch() {
     throw new Error("No wallet found.");
   }
 
-  console.log("Admin (wallet):", wallet.publicKey.toString());
+  console.log("Epoch Authority (wallet):", wallet.publicKey.toString());
   console.log("Deposit Mint:", depositMint.toString());
   console.log("Network:", connection.rpcEndpoint);
   console.log("");
@@ -82,15 +95,16 @@ async function increaseEpoch() {
   try {
     vaultState = await program.account.vaultState.fetch(vaultStatePda);
     console.log("✓ Current Exchange Rate:", vaultState.exchangeRate.toString());
-    console.log("  (Display: " + (vaultState.exchangeRate.toNumber() / EXCHANGE_RATE_SCALE).toFixed(6) + ")");
+    console.log("  (Display: " + formatRate(vaultState.exchangeRate) + ")");
     console.log("✓ Current Epoch:", vaultState.currentEpoch.toString());
-    console.log("✓ Admin:", vaultState.admin.toString());
+    console.log("✓ Epoch Authority:", vaultState.epochAuthority.toString());
+    console.log("✓ Min Epoch Duration:", vaultState.minEpochSlots.toString(), "slots /", vaultState.minEpochSeconds.toString(), "seconds");
     console.log("");
 
-    // Verify admin
-    if (!vaultState.admin.equals(wallet.publicKey)) {
-      console.error("✗ Error: Your wallet is not the admin of this vault.");
-      console.error("  Vault Admin:", vaultState.admin.toString());
+    // Verify epoch authority
+    if (!vaultState.epochAuthority.equals(wallet.publicKey)) {
+      console.error("✗ Error: Your wallet is not the epoch authority of this vault.");
+      console.error("  Epoch Authority:", vaultState.epochAuthority.toString());
       console.error("  Your Wallet:", wallet.publicKey.toString());
       process.exit(1);
     }
@@ -106,9 +120,9 @@ async function increaseEpoch() {
     process.exit(1);
   }
 
-  // Step 3: Use current exchange rate to increment epoch
+  // Step 3: Prepare to increment epoch
   console.log("Step 3: Preparing to increment epoch...");
-  const currentExchangeRate = vaultState.exchangeRate.toNumber();
+  const currentExchangeRate = vaultState.exchangeRate;
   const currentEpoch = vaultState.currentEpoch.toNumber();
   const newEpoch = currentEpoch + 1;
   
@@ -118,13 +132,13 @@ async function increaseEpoch() {
   console.log("✓ New Epoch:", newEpoch.toString());
   console.log("");
 
-  // Step 4: Increase the epoch by calling increase_rate with the same exchange rate
-  console.log("Step 4: Incrementing epoch (calling increase_rate with current rate)...");
+  // Step 4: Advance the epoch
+  console.log("Step 4: Incrementing epoch (calling advance_epoch)...");
   try {
     const tx = await program.methods
-      .increaseRate(new anchor.BN(currentExchangeRate))
+      .advanceEpoch()
       .accounts({
-        admin: walletKeypair.publicKey,
+        epochAuthority: walletKeypair.publicKey,
         vaultState: vaultStatePda,
       })
       .signers([walletKeypair])
@@ -138,7 +152,7 @@ async function increaseEpoch() {
     console.log("Step 5: Verifying vault state update...");
     const updatedVaultState = await program.account.vaultState.fetch(vaultStatePda);
     console.log("✓ Exchange Rate:", updatedVaultState.exchangeRate.toString());
-    console.log("  (Display: " + (updatedVaultState.exchangeRate.toNumber() / EXCHANGE_RATE_SCALE).toFixed(6) + ")");
+    console.log("  (Display: " + formatRate(updatedVaultState.exchangeRate) + ")");
     console.log("  (Unchanged as expected)");
     console.log("✓ New Epoch:", updatedVaultState.currentEpoch.toString());
     console.log("");
@@ -146,7 +160,7 @@ async function increaseEpoch() {
     console.log("=== Summary ===");
     console.log("✅ Epoch incremented successfully!");
     console.log("");
-    console.log("Exchange Rate:", (currentExchangeRate / EXCHANGE_RATE_SCALE).toFixed(6), "(unchanged)");
+    console.log("Exchange Rate:", formatRate(currentExchangeRate), "(unchanged)");
     console.log("Previous Epoch:", currentEpoch.toString());
     console.log("New Epoch:", updatedVaultState.currentEpoch.toString());
     console.log("");
@@ -155,6 +169,9 @@ async function increaseEpoch() {
     console.error("✗ Error incrementing epoch:", error);
     if (error instanceof Error) {
       console.error("  Message:", error.message);
+      if (error.message.includes("EpochTooEarly")) {
+        console.error("  The minimum epoch duration has not passed since the last epoch advance.");
+      }
     }
     throw error;
   }
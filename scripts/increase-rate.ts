@@ -2,6 +2,10 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { VaultProgram } from "../target/types/vault_program";
 import { PublicKey, Keypair, Connection, clusterApiUrl } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
 import * as path from "path";
 import * as os from "os";
 
@@ -10,8 +14,21 @@ const PROGRAM_ID = new PublicKey(
   "D7KrGPhkyWsqMRS7kQjaGzyT48nTaw4AopWM6qXXmBtg"
 );
 
-// Exchange rate scale factor (matches on-chain constant)
-const EXCHANGE_RATE_SCALE = 1_000_000;
+// Exchange rate scale factor (matches on-chain constant, 1e18)
+const EXCHANGE_RATE_DECIMALS = 18;
+const EXCHANGE_RATE_SCALE = new anchor.BN(10).pow(
+  new anchor.BN(EXCHANGE_RATE_DECIMALS)
+);
+
+// Format a scaled exchange rate for display (9 of its 18 decimals)
+function formatRate(rate: anchor.BN): string {
+  const fraction = rate
+    .mod(EXCHANGE_RATE_SCALE)
+    .toString()
+    .padStart(EXCHANGE_RATE_DECIMALS, "0")
+    .slice(0, 9);
+  return `${rate.div(EXCHANGE_RATE_SCALE).toString()}.${fraction}`;
+}
 
 // Set environment variables for Anchor to use
 process.env.ANCHOR_PROVIDER_URL = clusterApiUrl("devnet");
@@ -44,7 +61,7 @@ async function increaseRate() {
     console.error("\nExample:");
     console.error("  npx ts-node scripts/increase-rate.ts 3mJFZXLudQF1YgyoWJ5gB6Q97kRoVyE1C6UCihNc9xVN 1.1");
     console.error("  (This sets the exchange rate to 1.1, representing a 10% increase)");
-    console.error("\nNote: The exchange rate is scaled by 1,000,000 internally.");
+    console.error("\nNote: The exchange rate is scaled by 1e18 internally.");
     console.error("      Entering 1.1 means 1 IOU token = 1.1 deposit tokens.");
     process.exit(1);
   }
@@ -69,8 +86,9 @@ async function increaseRate() {
   }
 
   const rate = parseFloat(rateInput);
+  const rateMatch = /^(\d*)(?:\.(\d*))?$/.exec(rateInput.trim());
 
-  if (isNaN(rate) || rate <= 0) {
+  if (isNaN(rate) || rate <= 0 || !rateMatch) {
     console.error("✗ Invalid exchange rate. Please provide a positive number.");
     console.error("  Example: 1.1 for 10% increase, 1.05 for 5% increase");
     process.exit(1);
@@ -103,15 +121,15 @@ async function increaseRate() {
   try {
     vaultState = await program.account.vaultState.fetch(vaultStatePda);
     console.log("✓ Current Exchange Rate:", vaultState.exchangeRate.toString());
-    console.log("  (Display: " + (vaultState.exchangeRate.toNumber() / EXCHANGE_RATE_SCALE).toFixed(6) + ")");
+    console.log("  (Display: " + formatRate(vaultState.exchangeRate) + ")");
     console.log("✓ Current Epoch:", vaultState.currentEpoch.toString());
-    console.log("✓ Admin:", vaultState.admin.toString());
+    console.log("✓ Rate Manager:", vaultState.rateManager.toString());
     console.log("");
 
-    // Verify admin
-    if (!vaultState.admin.equals(wallet.publicKey)) {
-      console.error("✗ Error: Your wallet is not the admin of this vault.");
-      console.error("  Vault Admin:", vaultState.admin.toString());
+    // Verify rate manager
+    if (!vaultState.rateManager.equals(wallet.publicKey)) {
+      console.error("✗ Error: Your wallet is not the rate manager of this vault.");
+      console.error("  Vault Rate Manager:", vaultState.rateManager.toString());
       console.error("  Your Wallet:", wallet.publicKey.toString());
       process.exit(1);
     }
@@ -129,29 +147,48 @@ async function increaseRate() {
 
   // Step 3: Calculate scaled exchange rate
   console.log("Step 3: Calculating scaled exchange rate...");
-  const scaledRate = Math.floor(rate * EXCHANGE_RATE_SCALE);
+  // Scale the decimal input exactly; a float would lose precision at 18 decimals
+  const [, whole, fraction = ""] = rateMatch;
+  const scaledRate = new anchor.BN(whole || "0")
+    .mul(EXCHANGE_RATE_SCALE)
+    .add(
+      new anchor.BN(
+        fraction
+          .slice(0, EXCHANGE_RATE_DECIMALS)
+          .padEnd(EXCHANGE_RATE_DECIMALS, "0")
+      )
+    );
   console.log("✓ Scaled Exchange Rate:", scaledRate.toString());
-  console.log("  (Rate:", rate, "× Scale:", EXCHANGE_RATE_SCALE, "=", scaledRate, ")");
+  console.log("  (Rate:", rate, "× Scale:", EXCHANGE_RATE_SCALE.toString(), ")");
   console.log("");
 
-  // Validate that new rate is greater than current rate (optional check)
-  const currentRate = vaultState.exchangeRate.toNumber();
-  if (scaledRate <= currentRate) {
-    console.warn("⚠ Warning: New exchange rate is not greater than current rate.");
-    console.warn("  Current:", currentRate, "(" + (currentRate / EXCHANGE_RATE_SCALE).toFixed(6) + ")");
-    console.warn("  New:", scaledRate, "(" + rate.toFixed(6) + ")");
-    console.warn("  The transaction will still proceed, but this may not be the intended behavior.");
-    console.log("");
+  // Validate that new rate is not lower than current rate (decreases go through apply_loss)
+  const currentRate = vaultState.exchangeRate;
+  if (scaledRate.lt(currentRate)) {
+    console.error("✗ Error: New exchange rate is lower than the current rate.");
+    console.error("  Current:", currentRate.toString(), "(" + formatRate(currentRate) + ")");
+    console.error("  New:", scaledRate.toString(), "(" + formatRate(scaledRate) + ")");
+    console.error("  Rate decreases must go through apply_loss.");
+    process.exit(1);
   }
 
   // Step 4: Increase the exchange rate
+  // Performance fees (if any) are minted to the treasury's IOU token account
   console.log("Step 4: Increasing exchange rate...");
+  const treasuryIouTokenAccount = getAssociatedTokenAddressSync(
+    vaultState.iouMint,
+    vaultState.treasury,
+    true // Treasury may be a PDA
+  );
   try {
     const tx = await program.methods
-      .increaseRate(new anchor.BN(scaledRate))
+      .increaseRate(scaledRate)
       .accounts({
-        admin: walletKeypair.publicKey,
+        rateManager: walletKeypair.publicKey,
         vaultState: vaultStatePda,
+        iouMint: vaultState.iouMint,
+        treasuryIouTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([walletKeypair])
       .rpc();
@@ -164,17 +201,16 @@ async function increaseRate() {
     console.log("Step 5: Verifying vault state update...");
     const updatedVaultState = await program.account.vaultState.fetch(vaultStatePda);
     console.log("✓ New Exchange Rate:", updatedVaultState.exchangeRate.toString());
-    console.log("  (Display: " + (updatedVaultState.exchangeRate.toNumber() / EXCHANGE_RATE_SCALE).toFixed(6) + ")");
-    console.log("✓ New Epoch:", updatedVaultState.currentEpoch.toString());
+    console.log("  (Display: " + formatRate(updatedVaultState.exchangeRate) + ")");
+    console.log("✓ Epoch:", updatedVaultState.currentEpoch.toString(), "(unchanged - use increase-epoch.ts to advance)");
     console.log("");
 
     console.log("=== Summary ===");
     console.log("✅ Exchange rate increased successfully!");
     console.log("");
-    console.log("Previous Exchange Rate:", (currentRate / EXCHANGE_RATE_SCALE).toFixed(6));
-    console.log("New Exchange Rate:", (updatedVaultState.exchangeRate.toNumber() / EXCHANGE_RATE_SCALE).toFixed(6));
-    console.log("Previous Epoch:", vaultState.currentEpoch.toString());
-    console.log("New Epoch:", updatedVaultState.currentEpoch.toString());
+    console.log("Previous Exchange Rate:", formatRate(currentRate));
+    console.log("New Exchange Rate:", formatRate(updatedVaultState.exchangeRate));
+    console.log("Epoch:", updatedVaultState.currentEpoch.toString());
     console.log("");
     console.log("Transaction:", tx);
   } catch (error) {
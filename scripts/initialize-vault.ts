@@ -3,10 +3,13 @@ import { Program } from "@coral-xyz/anchor";
 import { VaultProgram } from "../target/types/vault_program";
 import {
   TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
   createMint,
   setAuthority,
   AuthorityType,
   getMint,
+  getOrCreateAssociatedTokenAccount,
 } from "@solana/spl-token";
 import {
   PublicKey,
@@ -76,8 +79,8 @@ async function initializeVault() {
     connection,
     walletKeypair, // payer
     walletKeypair.publicKey, // mint authority (will be transferred to vault_state PDA)
-    null, // freeze authority
-    9 // decimals (should match deposit mint)
+    null, // freeze authority (must be null, initialize rejects freezable IOU mints)
+    9 // decimals (must match deposit mint)
   );
   console.log("✓ IOU Mint:", iouMint.toString());
   console.log("");
@@ -121,6 +124,14 @@ async function initializeVault() {
         vaultState: vaultStatePda,
         depositMint: depositMint,
         iouMint: iouMint,
+        // Created by initialize: the vault_state's associated token account
+        vaultDepositTokenAccount: getAssociatedTokenAddressSync(
+          depositMint,
+          vaultStatePda,
+          true // allowOwnerOffCurve
+        ),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
       .signers([walletKeypair])
@@ -136,8 +147,45 @@ async function initializeVault() {
     console.log("Admin:", vaultState.admin.toString());
     console.log("Deposit Mint:", vaultState.depositMint.toString());
     console.log("IOU Mint:", vaultState.iouMint.toString());
+    console.log(
+      "Vault Token Account:",
+      vaultState.vaultTokenAccount.toString()
+    );
     console.log("Exchange Rate:", vaultState.exchangeRate.toString());
     console.log("Current Epoch:", vaultState.currentEpoch.toString());
+    console.log(
+      "Min Epoch Duration:",
+      vaultState.minEpochSlots.toString(),
+      "slots /",
+      vaultState.minEpochSeconds.toString(),
+      "seconds"
+    );
+    console.log("");
+
+    // Step 6: Create the treasury token accounts (the admin is the default treasury)
+    // Deposits and claims send fees to the deposit token account, performance fees
+    // are minted to the IOU token account
+    console.log("Step 6: Creating treasury token accounts...");
+    const treasuryDepositTokenAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      walletKeypair,
+      depositMint,
+      vaultState.treasury
+    );
+    const treasuryIouTokenAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      walletKeypair,
+      iouMint,
+      vaultState.treasury
+    );
+    console.log(
+      "✓ Treasury Deposit Token Account:",
+      treasuryDepositTokenAccount.address.toString()
+    );
+    console.log(
+      "✓ Treasury IOU Token Account:",
+      treasuryIouTokenAccount.address.toString()
+    );
     console.log("");
 
     console.log("=== Summary ===");